@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+
+/// Field names are shared by almost every entry of a source (journal JSON sends the same
+/// ~20 keys per line), so they are interned once instead of allocated per entry. Every
+/// field pushed takes the lock, and the readers of merged files contend for it; a short
+/// hash lookup is small next to parsing the line it came from.
+static KEYS: Lazy<Mutex<Interner>> = Lazy::new(|| Mutex::new(Interner::default()));
+
+/// Most keys interned at once. JSON and logfmt payloads can carry keys made of ids or
+/// values; past this, new keys are allocated per entry, and every so often the keys no
+/// entry holds any more (from a closed source) are dropped to make room.
+const MAX_KEYS: usize = 4096;
+
+#[derive(Default)]
+struct Interner {
+    keys: HashSet<Arc<str>>,
+    /// Keys not interned since the last clean-up
+    missed: usize,
+}
+
+impl Interner {
+    fn intern(&mut self, key: &str) -> Arc<str> {
+        if let Some(existing) = self.keys.get(key) {
+            return existing.clone();
+        }
+        let key: Arc<str> = Arc::from(key);
+        if self.keys.len() >= MAX_KEYS {
+            self.missed += 1;
+            if self.missed < MAX_KEYS {
+                return key;
+            }
+            self.missed = 0;
+            self.keys.retain(|k| Arc::strong_count(k) > 1);
+            if self.keys.len() >= MAX_KEYS {
+                return key;
+            }
+        }
+        self.keys.insert(key.clone());
+        key
+    }
+}
+
+fn intern(key: &str) -> Arc<str> {
+    KEYS.lock().unwrap().intern(key)
+}
+
+/// Compact per-entry key/value map, kept in insertion order.
+#[derive(Clone, Debug, Default)]
+pub struct Fields(Vec<(Arc<str>, Box<str>)>);
+
+impl Fields {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, key: &str, value: &str) {
+        self.0.push((intern(key), Box::from(value)));
+    }
+
+    /// All values stored under `key` (journal fields may repeat).
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0.iter().filter(move |(k, _)| &**k == key).map(|(_, v)| &**v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (&**k, &**v))
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for Fields {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut fields = Fields::new();
+        for (k, v) in iter {
+            fields.push(k.as_ref(), v.as_ref());
+        }
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fill `interner` to the cap with keys the caller keeps, as live entries would.
+    fn fill(interner: &mut Interner) -> Vec<Arc<str>> {
+        (0..MAX_KEYS).map(|i| interner.intern(&format!("key{}", i))).collect()
+    }
+
+    #[test]
+    fn keys_are_shared() {
+        let mut interner = Interner::default();
+        let a = interner.intern("_PID");
+        let b = interner.intern("_PID");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(interner.keys.len(), 1);
+    }
+
+    #[test]
+    fn keys_past_the_cap_are_not_interned() {
+        let mut interner = Interner::default();
+        let held = fill(&mut interner);
+        let a = interner.intern("request_id_1");
+        let b = interner.intern("request_id_1");
+        assert_eq!(&*a, "request_id_1");
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(interner.keys.len(), MAX_KEYS);
+        // Interned keys are still shared
+        assert!(Arc::ptr_eq(&interner.intern("key7"), &held[7]));
+    }
+
+    #[test]
+    fn keys_no_entry_holds_are_dropped_after_enough_misses() {
+        let mut interner = Interner::default();
+        let mut held = fill(&mut interner);
+        // A closed source: half its keys are no longer held by any entry
+        held.truncate(MAX_KEYS / 2);
+        for i in 0..MAX_KEYS - 1 {
+            interner.intern(&format!("miss{}", i));
+        }
+        assert_eq!(interner.keys.len(), MAX_KEYS);
+
+        // The next miss cleans up and interns the key in the room made
+        let key = interner.intern("new");
+        assert_eq!(interner.keys.len(), MAX_KEYS / 2 + 1);
+        assert_eq!(interner.missed, 0);
+        assert!(Arc::ptr_eq(&interner.intern("new"), &key));
+        assert!(Arc::ptr_eq(&interner.intern("key0"), &held[0]));
+    }
+
+    #[test]
+    fn clean_up_keeps_held_keys_and_still_misses_when_full() {
+        let mut interner = Interner::default();
+        let _held = fill(&mut interner);
+        for i in 0..MAX_KEYS {
+            interner.intern(&format!("miss{}", i));
+        }
+        // Every key was still held, so nothing could be dropped
+        assert_eq!(interner.keys.len(), MAX_KEYS);
+        assert_eq!(interner.missed, 0);
+        assert!(!interner.keys.contains("miss0"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use regex::Regex;
-use crate::analyzer::state::LogEntry;
+use crate::analyzer::state::{LogEntry, STATUS_FIELD};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CombineMode {
@@ -10,13 +10,141 @@ pub enum CombineMode {
     Not,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl FieldOp {
+    /// Operators as written in the filter text, longest first so `<=` wins over `<`.
+    const SYMBOLS: [(&'static str, FieldOp); 6] = [
+        ("!=", FieldOp::Ne),
+        ("<=", FieldOp::Le),
+        (">=", FieldOp::Ge),
+        ("=", FieldOp::Eq),
+        ("<", FieldOp::Lt),
+        (">", FieldOp::Gt),
+    ];
+}
+
+/// One `KEY=value` / `KEY!=value` / `KEY>=value` ... condition on an entry's fields.
+#[derive(Clone, Debug)]
+pub struct FieldFilter {
+    pub key: String,
+    pub op: FieldOp,
+    pub value: String,
+}
+
+impl FieldFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        let mut values = entry.fields.get_all(&self.key);
+        match self.op {
+            FieldOp::Eq => values.any(|v| v == self.value),
+            FieldOp::Ne => !values.any(|v| v == self.value),
+            FieldOp::Lt => values.any(|v| compare_values(v, &self.value).is_lt()),
+            FieldOp::Le => values.any(|v| compare_values(v, &self.value).is_le()),
+            FieldOp::Gt => values.any(|v| compare_values(v, &self.value).is_gt()),
+            FieldOp::Ge => values.any(|v| compare_values(v, &self.value).is_ge()),
+        }
+    }
+}
+
+/// Order two field values numerically when both are numbers (`latency_ms>250`), otherwise
+/// as text.
+fn compare_values(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Format a field condition so `parse_field_filters` reads it back, quoting the value
+/// when it contains whitespace or quotes.
+pub fn format_field_filter(key: &str, value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("{}=\"{}\"", key, value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        format!("{}={}", key, value)
+    }
+}
+
+/// Parse whitespace-separated field conditions, e.g. `_PID=1234 _TRANSPORT!=kernel MSG="a b"`
+/// or `http.status>=500`. All conditions must hold for an entry to pass.
+pub fn parse_field_filters(text: &str) -> Result<Vec<FieldFilter>, String> {
+    let mut filters = Vec::new();
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let key_end = rest.find(['=', '!', '<', '>'])
+            .ok_or_else(|| format!("expected KEY=value near \"{}\"", rest))?;
+        let key = &rest[..key_end];
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("invalid field name \"{}\"", key));
+        }
+        let (symbol, op) = FieldOp::SYMBOLS.into_iter()
+            .find(|(symbol, _)| rest[key_end..].starts_with(symbol))
+            .ok_or_else(|| format!("expected an operator after {}", key))?;
+        rest = &rest[key_end + symbol.len()..];
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            let end = end.ok_or_else(|| format!("unterminated quote after {}=", key))?;
+            rest = &quoted[end + 1..];
+            value
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+
+        filters.push(FieldFilter { key: key.to_string(), op, value });
+        rest = rest.trim_start();
+    }
+
+    Ok(filters)
+}
+
 pub struct FilterCriteria {
     /// Empty set means "all services". Non-empty means only matching services pass.
     pub units: HashSet<String>,
     pub max_priority: u8,
+    /// Empty set means "all facilities". Non-empty means only entries with a matching
+    /// syslog facility pass (entries without a facility are hidden).
+    pub facilities: HashSet<u8>,
+    /// Source files whose entries pass when several files are merged (empty = all).
+    pub sources: HashSet<String>,
+    /// HTTP status codes whose access log entries pass (empty = all). Entries without a
+    /// status are hidden while any is selected.
+    pub statuses: HashSet<u16>,
+    /// Inclusive time range in epoch microseconds. Entries without a timestamp are
+    /// hidden while either bound is set.
+    pub time_from: Option<i64>,
+    pub time_to: Option<i64>,
+    /// Conditions on per-entry fields (e.g. `_PID=1234`); all must match.
+    pub field_filters: Vec<FieldFilter>,
     pub pattern: Option<Regex>,
     pub pattern2: Option<Regex>,
     pub combine_mode: CombineMode,
+    /// Show entries for lines no format matched.
+    pub show_raw: bool,
 }
 
 impl Default for FilterCriteria {
@@ -24,9 +152,16 @@ impl Default for FilterCriteria {
         Self {
             units: HashSet::new(),
             max_priority: 7,
+            facilities: HashSet::new(),
+            sources: HashSet::new(),
+            statuses: HashSet::new(),
+            time_from: None,
+            time_to: None,
+            field_filters: Vec::new(),
             pattern: None,
             pattern2: None,
             combine_mode: CombineMode::Match,
+            show_raw: true,
         }
     }
 }
@@ -34,7 +169,12 @@ impl Default for FilterCriteria {
 impl FilterCriteria {
     /// Check if a LogEntry passes all filters
     pub fn matches(&self, entry: &LogEntry) -> bool {
-        if entry.priority > self.max_priority {
+        // Raw lines have no priority to compare; only the toggle hides them
+        if entry.raw {
+            if !self.show_raw {
+                return false;
+            }
+        } else if entry.priority > self.max_priority {
             return false;
         }
 
@@ -42,6 +182,37 @@ impl FilterCriteria {
             return false;
         }
 
+        if !self.facilities.is_empty()
+            && !entry.facility.is_some_and(|f| self.facilities.contains(&f))
+        {
+            return false;
+        }
+
+        if !self.sources.is_empty()
+            && !entry.source.as_deref().is_some_and(|s| self.sources.contains(s))
+        {
+            return false;
+        }
+
+        if !self.statuses.is_empty()
+            && !entry.fields.get_all(STATUS_FIELD).any(|s| s.parse().is_ok_and(|s| self.statuses.contains(&s)))
+        {
+            return false;
+        }
+
+        if self.time_from.is_some() || self.time_to.is_some() {
+            let Some(ts) = entry.timestamp_us else {
+                return false;
+            };
+            if self.time_from.is_some_and(|from| ts < from) || self.time_to.is_some_and(|to| ts > to) {
+                return false;
+            }
+        }
+
+        if !self.field_filters.iter().all(|f| f.matches(entry)) {
+            return false;
+        }
+
         let p1_match = self.pattern.as_ref()
             .map(|r| r.is_match(&entry.message))
             .unwrap_or(true);
@@ -95,3 +266,82 @@ impl FilterCriteria {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_with(fields: &[(&str, &str)]) -> LogEntry {
+        let mut entry = LogEntry::raw(1, "message");
+        for (key, value) in fields {
+            entry.fields.push(key, value);
+        }
+        entry
+    }
+
+    fn matches(filter: &str, entry: &LogEntry) -> bool {
+        parse_field_filters(filter).unwrap().iter().all(|f| f.matches(entry))
+    }
+
+    #[test]
+    fn operators_and_quoted_values_parse() {
+        let filters = parse_field_filters(r#"_PID=1234  http.status>=500 lat<=2.5 _TRANSPORT!=kernel MSG="a \"b\" c" n<1 n>0"#)
+            .unwrap();
+        let parsed: Vec<_> = filters.iter().map(|f| (f.key.as_str(), f.op, f.value.as_str())).collect();
+        assert_eq!(parsed, [
+            ("_PID", FieldOp::Eq, "1234"),
+            ("http.status", FieldOp::Ge, "500"),
+            ("lat", FieldOp::Le, "2.5"),
+            ("_TRANSPORT", FieldOp::Ne, "kernel"),
+            ("MSG", FieldOp::Eq, r#"a "b" c"#),
+            ("n", FieldOp::Lt, "1"),
+            ("n", FieldOp::Gt, "0"),
+        ]);
+    }
+
+    #[test]
+    fn malformed_filters_are_errors() {
+        assert!(parse_field_filters("just words").is_err());
+        assert!(parse_field_filters("=value").is_err());
+        assert!(parse_field_filters("key!value").is_err());
+        assert!(parse_field_filters(r#"MSG="unterminated"#).is_err());
+        assert_eq!(parse_field_filters("  ").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn formatted_filters_read_back() {
+        for value in ["plain", "two words", r#"say "hi" \ bye"#, ""] {
+            let filters = parse_field_filters(&format_field_filter("MSG", value)).unwrap();
+            assert_eq!(filters.len(), 1);
+            assert_eq!(filters[0].value, value);
+        }
+    }
+
+    #[test]
+    fn numbers_compare_as_numbers_and_the_rest_as_text() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_values("9", "10"), Ordering::Less);
+        assert_eq!(compare_values(" 250.0", "250"), Ordering::Equal);
+        assert_eq!(compare_values("-1", "0.5"), Ordering::Less);
+        // Either side not a number: byte order, so "9ms" sorts after "10ms"
+        assert_eq!(compare_values("9ms", "10ms"), Ordering::Greater);
+        assert_eq!(compare_values("10", "9a"), Ordering::Less);
+
+        let entry = entry_with(&[("latency_ms", "99"), ("level", "warn")]);
+        assert!(matches("latency_ms<100", &entry));
+        assert!(!matches("latency_ms>=100", &entry));
+        assert!(matches("level>error", &entry));
+    }
+
+    #[test]
+    fn not_equal_needs_every_value_to_differ() {
+        let entry = entry_with(&[("tags", "a"), ("tags", "b")]);
+        assert!(matches("tags=b", &entry));
+        assert!(!matches("tags!=b", &entry));
+        assert!(matches("tags!=c", &entry));
+        // An entry without the field passes != and fails everything else
+        assert!(matches("missing!=x", &entry));
+        assert!(!matches("missing=x", &entry));
+        assert!(!matches("missing<x", &entry));
+    }
+}
@@ -0,0 +1,101 @@
+//! JSON application payloads in MESSAGE (`{"level":"error","http":{"status":502}}`),
+//! flattened at ingest into dotted-path fields such as `http.status`.
+
+use serde_json::Value;
+
+/// Fields kept per message, so one huge document can't balloon an entry.
+const MAX_FIELDS: usize = 256;
+
+/// Flatten a message holding a JSON object into `(path, value)` pairs, or None if it isn't
+/// one. Array elements repeat their parent's path (`tags=a`, `tags=b`), like repeated
+/// journal fields, so `tags=a` matches any element.
+pub fn flatten(message: &str) -> Option<Vec<(String, String)>> {
+    let trimmed = message.trim();
+    if !trimmed.starts_with('{') {
+        return None;
+    }
+    let Value::Object(map) = serde_json::from_str(trimmed).ok()? else {
+        return None;
+    };
+
+    let mut fields = Vec::new();
+    for (key, value) in &map {
+        flatten_into(key, value, &mut fields);
+    }
+    Some(fields)
+}
+
+fn flatten_into(path: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    if out.len() >= MAX_FIELDS {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_into(&format!("{}.{}", path, key), value, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                flatten_into(path, item, out);
+            }
+        }
+        Value::String(s) => out.push((path.to_string(), s.clone())),
+        other => out.push((path.to_string(), other.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::logfmt::pairs_priority;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn nested_objects_and_arrays_flatten_to_paths() {
+        let fields = flatten(r#" {"http":{"status":502,"ok":false},"tags":["a",["b","c"]],"hops":[{"ip":"10.0.0.1"},{"ip":"10.0.0.2"}],"user":null} "#)
+            .unwrap();
+        // Keys come out sorted; array elements keep their order
+        assert_eq!(fields, pairs(&[
+            ("hops.ip", "10.0.0.1"),
+            ("hops.ip", "10.0.0.2"),
+            ("http.ok", "false"),
+            ("http.status", "502"),
+            ("tags", "a"),
+            ("tags", "b"),
+            ("tags", "c"),
+            ("user", "null"),
+        ]));
+    }
+
+    #[test]
+    fn only_objects_are_payloads() {
+        assert_eq!(flatten("[1, 2]"), None);
+        assert_eq!(flatten("{not json}"), None);
+        assert_eq!(flatten("level=info msg=hi"), None);
+        assert_eq!(flatten("{}"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn huge_documents_are_capped() {
+        let items: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let fields = flatten(&format!(r#"{{"ids":[{}]}}"#, items.join(","))).unwrap();
+        assert_eq!(fields.len(), MAX_FIELDS);
+    }
+
+    #[test]
+    fn level_keys_give_the_priority() {
+        let priority = |json: &str| pairs_priority(&flatten(json).unwrap());
+        assert_eq!(priority(r#"{"msg":"x","level":"error"}"#), Some(3));
+        assert_eq!(priority(r#"{"Severity":"WARNING"}"#), Some(4));
+        assert_eq!(priority(r#"{"log":{"level":"debug"}}"#), Some(7));
+        // bunyan / pino numeric levels
+        assert_eq!(priority(r#"{"level":50}"#), Some(3));
+        assert_eq!(priority(r#"{"level":30}"#), Some(6));
+        assert_eq!(priority(r#"{"http":{"level":"error"}}"#), None);
+        assert_eq!(priority(r#"{"level":"verbose"}"#), None);
+    }
+}
@@ -0,0 +1,117 @@
+//! logfmt / `key=value` payloads inside messages, e.g.
+//! `level=warn msg="slow query" req_id=abc latency_ms=42`.
+
+/// Keys whose value names the entry's severity (`log.level` is the Elastic Common Schema
+/// path once JSON payloads are flattened).
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity", "loglevel", "log.level"];
+
+/// Extract the `key=value` pairs of a logfmt message. Values may be double-quoted with
+/// backslash escapes. Prose that happens to hold a pair or two ("retrying with timeout=5s")
+/// isn't logfmt, so nothing is extracted unless there are at least two pairs and they
+/// outnumber the free-text words around them.
+pub fn extract_pairs(message: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut words = 0;
+    let mut rest = message.trim_start();
+
+    while !rest.is_empty() {
+        let key_len = rest.find(|c: char| !is_key_char(c)).unwrap_or(rest.len());
+        let starts_like_key = rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if starts_like_key && rest[key_len..].starts_with('=') {
+            let key = &rest[..key_len];
+            let (value, after) = split_value(&rest[key_len + 1..]);
+            pairs.push((key.to_string(), value));
+            rest = after;
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = &rest[end..];
+            words += 1;
+        }
+        rest = rest.trim_start();
+    }
+    if pairs.len() < 2 || pairs.len() <= words {
+        pairs.clear();
+    }
+    pairs
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// Split a (possibly quoted) value off the front of `text`, returning it and the remainder.
+/// An unterminated quote takes the rest of the message.
+fn split_value(text: &str) -> (String, &str) {
+    let Some(quoted) = text.strip_prefix('"') else {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        return (text[..end].to_string(), &text[end..]);
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, c)) => value.push(c),
+                None => value.push('\\'),
+            },
+            '"' => return (value, &quoted[i + 1..]),
+            _ => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+/// Priority (0 = emerg .. 7 = debug) named by a `level=` style value, as used by logfmt,
+/// logrus, zap, slog, log4j and friends. Numbers 0-7 are syslog severities; bunyan and
+/// pino use 10 (trace) to 60 (fatal).
+pub fn level_priority(level: &str) -> Option<u8> {
+    let priority = match level.trim().to_ascii_lowercase().as_str() {
+        "emerg" | "emergency" | "panic" => 0,
+        "alert" => 1,
+        "crit" | "critical" | "fatal" | "dpanic" => 2,
+        "err" | "error" | "eror" => 3,
+        "warn" | "warning" | "wrn" => 4,
+        "notice" => 5,
+        "info" | "information" | "inf" => 6,
+        "debug" | "dbg" | "trace" | "trc" => 7,
+        "10" | "20" => 7,
+        "30" => 6,
+        "40" => 4,
+        "50" => 3,
+        "60" => 2,
+        other => return other.parse::<u8>().ok().filter(|p| *p <= 7),
+    };
+    Some(priority)
+}
+
+/// Priority from the first level-like key among `pairs`.
+pub fn pairs_priority(pairs: &[(String, String)]) -> Option<u8> {
+    pairs.iter()
+        .find(|(key, _)| LEVEL_KEYS.iter().any(|k| key.eq_ignore_ascii_case(k)))
+        .and_then(|(_, value)| level_priority(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logfmt_message_is_extracted() {
+        let pairs = extract_pairs(r#"level=warn msg="slow \"select\" query" took 42ms req_id=abc"#);
+        assert_eq!(pairs, [
+            ("level".to_string(), "warn".to_string()),
+            ("msg".to_string(), r#"slow "select" query"#.to_string()),
+            ("req_id".to_string(), "abc".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn prose_with_a_few_pairs_is_not_logfmt() {
+        assert!(extract_pairs("retrying with timeout=5s").is_empty());
+        assert!(extract_pairs("connection reset by peer, retrying in 5s with level=debug attempt=3").is_empty());
+        assert!(extract_pairs("level=info").is_empty());
+    }
+}
@@ -1,5 +1,12 @@
 pub mod state;
+pub mod fields;
 pub mod filter;
+pub mod timezone;
+pub mod logfmt;
+pub mod json_payload;
+pub mod priority_rules;
 
-pub use state::{LogStore, LogEntry};
-pub use filter::{FilterCriteria, CombineMode};
+pub use state::{LogStore, LogEntry, STATUS_FIELD, parse_timestamp_text, parse_timestamp_in_zone};
+pub use fields::Fields;
+pub use timezone::{parse_time_zone, local_to_micros, micros_in_zone, zone_label};
+pub use filter::{FilterCriteria, CombineMode, format_field_filter, parse_field_filters};
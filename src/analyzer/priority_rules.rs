@@ -0,0 +1,206 @@
+//! Rules guessing a priority from the message, for sources that don't report one (plain
+//! syslog without `<PRI>`, container output, dmesg, user formats without a priority group).
+//!
+//! They live in `~/.config/jlog/priority_rules.json`, replacing the built-in list when the
+//! file exists. Rules are tried in order and the first match wins; a rule with a `service`
+//! only applies to that service's entries.
+//!
+//! ```json
+//! [
+//!     { "regex": "\\bE\\d{4}\\b", "priority": 3, "service": "myapp" },
+//!     { "regex": "(?i)\\b(0|no) errors\\b", "priority": 6 },
+//!     { "regex": "(?i)error|failed", "priority": 3 }
+//! ]
+//! ```
+
+use std::path::PathBuf;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::analyzer::LogEntry;
+
+/// One entry of priority_rules.json, as written by the user.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleDef {
+    pub regex: String,
+    /// Syslog severity (0-7) for messages the regex matches.
+    pub priority: u8,
+    /// Service the rule is limited to (empty = every service).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub service: String,
+}
+
+impl RuleDef {
+    fn new(regex: &str, priority: u8) -> Self {
+        Self { regex: regex.to_string(), priority, service: String::new() }
+    }
+}
+
+/// The rules used when priority_rules.json doesn't exist: the usual keywords, checked
+/// case-insensitively, with "no errors" / "0 failures" kept from counting as errors.
+pub fn default_defs() -> Vec<RuleDef> {
+    vec![
+        RuleDef::new(r"(?i)\b(no|0|zero) (errors?|failures?)\b", 6),
+        RuleDef::new(r"(?i)panic|fatal|critical", 2),
+        RuleDef::new(r"(?i)error|failed|failure|cannot|unable to|segfault|exception", 3),
+        RuleDef::new(r"(?i)warn|timeout|timed out|retrying|deprecated|denied|refused", 4),
+        RuleDef::new(r"(?i)started|stopped|connected|loaded|finished", 5),
+    ]
+}
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    priority: u8,
+    service: Option<String>,
+}
+
+/// Compiled rules.
+#[derive(Debug)]
+pub struct PriorityRules {
+    rules: Vec<Rule>,
+}
+
+impl Default for PriorityRules {
+    fn default() -> Self {
+        Self::compile(&default_defs()).expect("built-in priority rules compile")
+    }
+}
+
+impl PriorityRules {
+    pub fn compile(defs: &[RuleDef]) -> Result<Self, String> {
+        let rules = defs.iter()
+            .enumerate()
+            .map(|(i, def)| {
+                let regex = Regex::new(&def.regex).map_err(|e| format!("rule {}: {}", i + 1, e))?;
+                if def.priority > 7 {
+                    return Err(format!("rule {}: priority {} is not 0-7", i + 1, def.priority));
+                }
+                let service = Some(def.service.trim().to_string()).filter(|s| !s.is_empty());
+                Ok(Rule { regex, priority: def.priority, service })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Index of the first rule matching a message of `service`.
+    pub fn matching_rule(&self, service: &str, message: &str) -> Option<usize> {
+        self.rules.iter().position(|rule| {
+            rule.service.as_ref().is_none_or(|s| s == service) && rule.regex.is_match(message)
+        })
+    }
+
+    /// Priority of the first matching rule, or None if no rule matches.
+    pub fn infer(&self, service: &str, message: &str) -> Option<u8> {
+        self.matching_rule(service, message).map(|i| self.rules[i].priority)
+    }
+
+    /// Set the priority of an entry whose source gave none. It keeps the parser's default
+    /// (INFO, or WARN for container stderr) when no rule matches; keeping stderr at WARN or
+    /// above is up to the caller.
+    pub fn apply(&self, entry: &mut LogEntry) {
+        if entry.priority_inferred && let Some(priority) = self.infer(&entry.service, &entry.message) {
+            entry.priority = priority;
+        }
+    }
+}
+
+pub fn rules_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_default();
+    PathBuf::from(home).join(".config").join("jlog").join("priority_rules.json")
+}
+
+/// The rules as written in priority_rules.json, or the built-in ones if there is none.
+pub fn load_defs() -> Result<Vec<RuleDef>, String> {
+    let path = rules_path();
+    match std::fs::read_to_string(&path) {
+        Ok(data) => serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(default_defs()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Read and compile priority_rules.json.
+pub fn load_rules() -> Result<PriorityRules, String> {
+    let path = rules_path();
+    PriorityRules::compile(&load_defs()?).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save_defs(defs: &[RuleDef]) -> Result<(), String> {
+    let path = rules_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    let data = serde_json::to_string_pretty(defs).map_err(|e| e.to_string())?;
+    std::fs::write(&path, data).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(regex: &str, priority: u8, service: &str) -> RuleDef {
+        RuleDef { regex: regex.to_string(), priority, service: service.to_string() }
+    }
+
+    #[test]
+    fn built_in_rules() {
+        let rules = PriorityRules::default();
+        assert_eq!(rules.infer("make", "0 errors found"), Some(6));
+        assert_eq!(rules.infer("make", "build finished with no errors"), Some(6));
+        assert_eq!(rules.infer("make", "3 errors found"), Some(3));
+        assert_eq!(rules.infer("app", "FATAL: disk full"), Some(2));
+        assert_eq!(rules.infer("app", "connection timed out, retrying"), Some(4));
+        assert_eq!(rules.infer("app", "listening on :8080"), None);
+    }
+
+    #[test]
+    fn first_match_wins_and_services_scope_rules() {
+        let mut defs = vec![rule(r"\bE\d{4}\b", 3, "rustc")];
+        defs.extend(default_defs());
+        let rules = PriorityRules::compile(&defs).unwrap();
+        assert_eq!(rules.infer("rustc", "E0412: cannot find type"), Some(3));
+        assert_eq!(rules.matching_rule("rustc", "E0412: cannot find type"), Some(0));
+        // Other services skip the scoped rule and fall through to the built-in ones
+        assert_eq!(rules.matching_rule("app", "E0412: cannot find type"), Some(3));
+        assert_eq!(rules.infer("app", "code E0412"), None);
+
+        // Order decides between overlapping rules
+        let rules = PriorityRules::compile(&[rule("warn", 4, ""), rule("warn|error", 3, "")]).unwrap();
+        assert_eq!(rules.infer("app", "warning"), Some(4));
+        assert_eq!(rules.infer("app", "error"), Some(3));
+    }
+
+    #[test]
+    fn compile_rejects_bad_rules() {
+        let err = PriorityRules::compile(&[rule("ok", 3, ""), rule("(unclosed", 3, "")]).unwrap_err();
+        assert!(err.starts_with("rule 2:"), "{}", err);
+        let err = PriorityRules::compile(&[rule("ok", 8, "")]).unwrap_err();
+        assert_eq!(err, "rule 1: priority 8 is not 0-7");
+        // Whitespace-only services mean every service
+        let rules = PriorityRules::compile(&[rule("x", 2, "  ")]).unwrap();
+        assert_eq!(rules.infer("any", "x"), Some(2));
+    }
+
+    #[test]
+    fn apply_only_touches_inferred_priorities() {
+        let rules = PriorityRules::default();
+        let mut entry = LogEntry::raw(1, "request failed");
+        entry.priority_inferred = true;
+        rules.apply(&mut entry);
+        assert_eq!(entry.priority, 3);
+
+        let mut entry = LogEntry::raw(1, "request failed");
+        entry.priority = 5;
+        rules.apply(&mut entry);
+        assert_eq!(entry.priority, 5);
+
+        // No match keeps the parser's default
+        let mut entry = LogEntry::raw(1, "listening");
+        entry.priority_inferred = true;
+        entry.priority = 4;
+        rules.apply(&mut entry);
+        assert_eq!(entry.priority, 4);
+    }
+}
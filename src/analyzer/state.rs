@@ -1,16 +1,104 @@
 use std::collections::BTreeSet;
+use std::sync::Arc;
+use jiff::tz::TimeZone;
+use crate::analyzer::{Fields, local_to_micros};
 
 pub struct LogEntry {
     pub line_num: usize,
-    pub timestamp: String,
+    /// Microseconds since the Unix epoch (UTC), or None if the source line had no wall-clock time.
+    pub timestamp_us: Option<i64>,
     pub priority: u8,
+    /// The source gave no priority; `priority` was guessed from the message.
+    pub priority_inferred: bool,
+    /// Syslog facility (0 = kern .. 23 = local7), when the source carried one.
+    pub facility: Option<u8>,
     pub service: String,
     pub message: String,
+    /// Original bytes when the source sent the message as binary; `message` then holds
+    /// a lossy UTF-8 decoding.
+    pub raw_message: Option<Box<[u8]>>,
+    /// Additional key/value fields carried by the source line (shown in Row Detail).
+    pub fields: Fields,
+    /// File the entry came from when several files are merged into one view.
+    pub source: Option<Arc<str>>,
+    /// The line matched no format and is kept as it was (see `LogEntry::raw`).
+    pub raw: bool,
 }
 
+impl LogEntry {
+    /// Entry for a line no format matched: the whole line is the message, with no time and
+    /// service "unknown". Its priority is unknown; `priority` only holds INFO as a placeholder,
+    /// which the priority filter ignores.
+    pub fn raw(line_num: usize, line: &str) -> Self {
+        Self {
+            line_num,
+            timestamp_us: None,
+            priority: 6,
+            priority_inferred: false,
+            facility: None,
+            service: "unknown".to_string(),
+            message: line.trim_end().to_string(),
+            raw_message: None,
+            fields: Fields::new(),
+            source: None,
+            raw: true,
+        }
+    }
+
+    /// Full-precision "YYYY-MM-DD HH:MM:SS[.ffffff]" (UTC) used for saving and copying.
+    /// Empty when the entry has no timestamp.
+    pub fn timestamp_text(&self) -> String {
+        self.timestamp_us.map(format_timestamp_us).unwrap_or_default()
+    }
+}
+
+/// Format epoch microseconds as "YYYY-MM-DD HH:MM:SS", adding ".ffffff" only when
+/// there is a sub-second part.
+pub fn format_timestamp_us(us: i64) -> String {
+    let Some(dt) = chrono::DateTime::from_timestamp_micros(us) else {
+        return String::new();
+    };
+    if us % 1_000_000 == 0 {
+        dt.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        dt.format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+    }
+}
+
+/// Parse a UTC timestamp typed by the user or written by `format_timestamp_us`
+/// ("2026-10-11 22:14:15.123", "2026-10-11T22:14", "2026-10-11") into epoch microseconds.
+pub fn parse_timestamp_text(text: &str) -> Option<i64> {
+    parse_naive_timestamp(text).map(|dt| dt.and_utc().timestamp_micros())
+}
+
+/// Like `parse_timestamp_text`, but for a wall-clock time in `tz` (the display zone).
+pub fn parse_timestamp_in_zone(text: &str, tz: &TimeZone) -> Option<i64> {
+    parse_naive_timestamp(text).and_then(|dt| local_to_micros(dt, tz))
+}
+
+fn parse_naive_timestamp(text: &str) -> Option<chrono::NaiveDateTime> {
+    let text = text.trim().replacen('T', " ", 1);
+    for fmt in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(&text, fmt) {
+            return Some(dt);
+        }
+    }
+    chrono::NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+}
+
+/// Field holding an HTTP response status, which access log parsers set and the status
+/// facet filters on.
+pub const STATUS_FIELD: &str = "HTTP_STATUS";
+
 pub struct LogStore {
     pub entries: Vec<LogEntry>,
     pub services: BTreeSet<String>,
+    pub facilities: BTreeSet<u8>,
+    pub sources: BTreeSet<String>,
+    /// HTTP status codes seen in access logs
+    pub statuses: BTreeSet<u16>,
 }
 
 impl LogStore {
@@ -18,10 +106,78 @@ impl LogStore {
         Self {
             entries: Vec::new(),
             services: BTreeSet::new(),
+            facilities: BTreeSet::new(),
+            sources: BTreeSet::new(),
+            statuses: BTreeSet::new(),
         }
     }
 
     pub fn service_names(&self) -> Vec<String> {
         self.services.iter().cloned().collect()
     }
+
+    /// Stable-sort entries by timestamp (entries without one go first).
+    /// Returns the old-to-new index mapping so callers can remap stored indices.
+    pub fn sort_by_time(&mut self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by_key(|&i| self.entries[i].timestamp_us);
+
+        let mut old_to_new = vec![0; order.len()];
+        for (new_idx, &old_idx) in order.iter().enumerate() {
+            old_to_new[old_idx] = new_idx;
+        }
+
+        let mut slots: Vec<Option<LogEntry>> = self.entries.drain(..).map(Some).collect();
+        self.entries = order.iter().map(|&i| slots[i].take().unwrap()).collect();
+        old_to_new
+    }
+
+    pub fn facility_codes(&self) -> Vec<u8> {
+        self.facilities.iter().copied().collect()
+    }
+
+    pub fn source_names(&self) -> Vec<String> {
+        self.sources.iter().cloned().collect()
+    }
+
+    pub fn status_codes(&self) -> Vec<u16> {
+        self.statuses.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2023-10-11 16:00:00 UTC
+    const TIME_US: i64 = 1_697_040_000_000_000;
+
+    #[test]
+    fn sub_seconds_are_only_written_when_present() {
+        assert_eq!(format_timestamp_us(TIME_US), "2023-10-11 16:00:00");
+        assert_eq!(format_timestamp_us(TIME_US + 1), "2023-10-11 16:00:00.000001");
+        assert_eq!(format_timestamp_us(TIME_US + 123_000), "2023-10-11 16:00:00.123000");
+        assert_eq!(format_timestamp_us(-500_000), "1969-12-31 23:59:59.500000");
+        assert_eq!(LogEntry::raw(1, "no time").timestamp_text(), "");
+    }
+
+    #[test]
+    fn typed_timestamps_parse() {
+        assert_eq!(parse_timestamp_text("2023-10-11 16:00:00"), Some(TIME_US));
+        assert_eq!(parse_timestamp_text(" 2023-10-11T16:00:00.25 "), Some(TIME_US + 250_000));
+        assert_eq!(parse_timestamp_text("2023-10-11 16:00"), Some(TIME_US));
+        assert_eq!(parse_timestamp_text("2023-10-11"), Some(TIME_US - 16 * 3_600_000_000));
+        assert_eq!(parse_timestamp_text("2023-10-11 25:00:00"), None);
+        assert_eq!(parse_timestamp_text("yesterday"), None);
+
+        let berlin = crate::analyzer::parse_time_zone("Europe/Berlin").unwrap();
+        assert_eq!(parse_timestamp_in_zone("2023-10-11 18:00:00", &berlin), Some(TIME_US));
+    }
+
+    #[test]
+    fn saved_text_round_trips() {
+        for us in [TIME_US, TIME_US + 1, TIME_US + 999_999, -1, 0] {
+            assert_eq!(parse_timestamp_text(&format_timestamp_us(us)), Some(us), "{}", us);
+        }
+    }
 }
@@ -0,0 +1,44 @@
+use jiff::tz::TimeZone;
+
+/// Parse a zone typed by the user: "UTC", "local" (the viewer's zone) or an IANA name
+/// such as "Europe/Berlin". Empty means UTC.
+pub fn parse_time_zone(text: &str) -> Result<TimeZone, String> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("utc") {
+        Ok(TimeZone::UTC)
+    } else if text.eq_ignore_ascii_case("local") {
+        Ok(TimeZone::system())
+    } else {
+        TimeZone::get(text).map_err(|_| format!("Unknown time zone: {}", text))
+    }
+}
+
+/// Interpret a wall-clock time written in `tz` as epoch microseconds. Times skipped by a
+/// DST change resolve forward; repeated ones take the earlier instant.
+pub fn local_to_micros(dt: chrono::NaiveDateTime, tz: &TimeZone) -> Option<i64> {
+    use chrono::{Datelike, Timelike};
+    let civil = jiff::civil::DateTime::new(
+        i16::try_from(dt.year()).ok()?,
+        dt.month() as i8,
+        dt.day() as i8,
+        dt.hour() as i8,
+        dt.minute() as i8,
+        dt.second() as i8,
+        dt.nanosecond().min(999_999_999) as i32,
+    )
+    .ok()?;
+    let ts = tz.to_ambiguous_timestamp(civil).compatible().ok()?;
+    Some(ts.as_microsecond())
+}
+
+/// Epoch microseconds as wall-clock time in `tz`, for formatting.
+pub fn micros_in_zone(us: i64, tz: &TimeZone) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let offset = tz.to_offset(jiff::Timestamp::from_microsecond(us).ok()?);
+    let offset = chrono::FixedOffset::east_opt(offset.seconds())?;
+    Some(chrono::DateTime::from_timestamp_micros(us)?.with_timezone(&offset))
+}
+
+/// Short name for `tz` in column headers: its IANA name, or "local" for an unnamed system zone.
+pub fn zone_label(tz: &TimeZone) -> &str {
+    tz.iana_name().unwrap_or("local")
+}
@@ -3,14 +3,21 @@ use eframe::egui;
 use regex::Regex;
 use std::collections::HashSet;
 
-use crate::analyzer::{LogStore, FilterCriteria};
+use crate::analyzer::{LogStore, FilterCriteria, STATUS_FIELD};
+use crate::analyzer::priority_rules::{self, RuleDef};
 use crate::background::{BackgroundMessage, BackgroundCommand};
 use crate::ui::connection_dialog::ConnectionDialog;
 use crate::ui::filter_bar::FilterBar;
-use crate::ui::log_viewer::{LogViewer, priority_label, priority_color};
+use crate::ui::log_viewer::{LogViewer, DisplayZone, TimeFormat, entry_priority_label, format_entry_time, priority_color};
 use crate::ui::open_file_dialog::OpenFileDialog;
+use crate::ui::priority_rules_dialog::PriorityRulesDialog;
 use crate::ui::save_settings::{SaveSettings, SaveSettingsDialog, load_settings, save_settings_to_disk};
-use crate::workers::{file_reader, log_writer, ssh_reader};
+use crate::formats::custom;
+use crate::journalctl::SyslogClock;
+use crate::workers::{file_reader, log_writer, ssh_reader, stdin_reader};
+use crate::workers::line_parser::LineFormat;
+use crate::workers::multiline::MultilineRules;
+use crate::workers::parse_options::ParseOptions;
 
 struct FindState {
     active: bool,
@@ -39,18 +46,37 @@ pub struct JlogApp {
 
     is_loading: bool,
     is_connected: bool,
+    /// Streaming from stdin (`jlog -`) until EOF
+    is_reading_stdin: bool,
+    /// Following a local file for appended lines (tail -F)
+    is_following: bool,
     status_message: String,
     total_lines: usize,
 
     save_settings: SaveSettings,
     save_settings_dialog: SaveSettingsDialog,
+    priority_rules_dialog: PriorityRulesDialog,
     current_host: String,
 
     /// Last SSH config used (for reconnect)
     last_ssh_config: Option<ssh_reader::SshConfig>,
-
-    /// File to load on first frame (from CLI argument)
-    pending_file: Option<String>,
+    /// Files or directories last loaded (for re-parsing with another format)
+    last_paths: Vec<String>,
+    /// Format the current source was read as, and the share of sniffed lines it matched
+    detected_format: Option<(String, Option<f32>)>,
+    /// Lines of the current source no format matched, and a sample of them
+    parse_failures: Option<(usize, Vec<String>)>,
+    /// Settings of the current source that couldn't be applied (a broken priority_rules.json,
+    /// a profile's format missing from formats.json)
+    source_warnings: Vec<String>,
+    /// User format names for the status bar's re-parse menu, read when it opens
+    format_menu_names: Option<Vec<String>>,
+
+    /// Files or directories to load on first frame (from CLI arguments)
+    pending_files: Vec<String>,
+
+    /// Read stdin on first frame (from CLI argument `-`)
+    pending_stdin: bool,
 
     /// SSH profile name to connect on first frame (from CLI --profile/-p argument)
     pending_ssh_profile: Option<String>,
@@ -66,22 +92,36 @@ pub struct JlogApp {
     bookmarks: HashSet<usize>,
     /// Whether the bookmark/timeline window is open
     show_bookmarks: bool,
+
+    /// IANA zone name typed in View > Time Zone, and why it was rejected
+    zone_text: String,
+    zone_error: Option<String>,
 }
 
 impl JlogApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // Parse CLI arguments: jlog [<file>] [--profile|-p <name>]
+        // Parse CLI arguments: jlog [--follow|-f] [--year <year>] [--tz <zone>] [--format <format>]
+        //                          [<file|dir>... | -] [--profile|-p <name>]
         let mut args = std::env::args().skip(1);
-        let mut pending_file = None;
+        let mut open_file_dialog = OpenFileDialog::default();
+        let mut pending_files = Vec::new();
+        let mut pending_stdin = false;
         let mut pending_ssh_profile = None;
         while let Some(arg) = args.next() {
             if arg == "--profile" || arg == "-p" {
                 pending_ssh_profile = args.next();
-            } else if pending_file.is_none() {
-                let path = std::path::Path::new(&arg);
-                if path.exists() && path.is_file() {
-                    pending_file = Some(arg);
-                }
+            } else if arg == "--follow" || arg == "-f" {
+                open_file_dialog.follow = true;
+            } else if arg == "--year" {
+                open_file_dialog.year = args.next().unwrap_or_default();
+            } else if arg == "--tz" {
+                open_file_dialog.timezone = args.next().unwrap_or_default();
+            } else if arg == "--format" {
+                open_file_dialog.format = args.next().unwrap_or_default();
+            } else if arg == "-" {
+                pending_stdin = true;
+            } else if std::path::Path::new(&arg).exists() {
+                pending_files.push(arg);
             }
         }
 
@@ -90,7 +130,7 @@ impl JlogApp {
             filter: FilterCriteria::default(),
             filtered_indices: Vec::new(),
 
-            open_file_dialog: OpenFileDialog::default(),
+            open_file_dialog,
             connection_dialog: ConnectionDialog::default(),
             filter_bar: FilterBar::default(),
             log_viewer: LogViewer::default(),
@@ -100,16 +140,26 @@ impl JlogApp {
 
             is_loading: false,
             is_connected: false,
+            is_reading_stdin: false,
+            is_following: false,
             status_message: "Ready - File > Open or Connect SSH".to_string(),
             total_lines: 0,
 
             save_settings: load_settings(),
             save_settings_dialog: SaveSettingsDialog::default(),
+            priority_rules_dialog: PriorityRulesDialog::default(),
             current_host: "local".to_string(),
 
             last_ssh_config: None,
+            last_paths: Vec::new(),
+            detected_format: None,
+            parse_failures: None,
+            source_warnings: Vec::new(),
+            format_menu_names: None,
+
+            pending_files,
 
-            pending_file,
+            pending_stdin,
 
             pending_ssh_profile,
 
@@ -128,18 +178,42 @@ impl JlogApp {
 
             bookmarks: HashSet::new(),
             show_bookmarks: false,
+
+            zone_text: String::new(),
+            zone_error: None,
         }
     }
 
-    fn load_file(&mut self, path: String) {
+    /// Load one file, or merge several files / directories into one time-ordered view.
+    fn load_files(&mut self, mut paths: Vec<String>) {
         self.reset_state();
+        self.last_paths = paths.clone();
         self.current_host = "local".to_string();
         self.is_loading = true;
-        self.status_message = format!("Loading: {}", path);
 
+        let multiline = self.open_file_dialog.multiline_rules().unwrap_or_default();
+        let options = self.open_file_dialog.parse_options().unwrap_or_default();
+        self.source_warnings = rules_warning(&options).into_iter().collect();
+        self.log_viewer.set_source_zone(options.clock.tz.clone());
         let (tx, rx) = unbounded();
         self.bg_receiver = Some(rx);
-        file_reader::read_file(path, tx);
+        if paths.len() == 1 && !std::path::Path::new(&paths[0]).is_dir() {
+            let path = paths.remove(0);
+            self.status_message = format!("Loading: {}", path);
+            // -f on the command line skips the dialog's check for journal and export files
+            let follow = if self.open_file_dialog.follow && file_reader::is_followable(&path) {
+                let (cmd_tx, cmd_rx) = unbounded();
+                self.bg_cmd_sender = Some(cmd_tx);
+                self.is_following = true;
+                Some(cmd_rx)
+            } else {
+                None
+            };
+            file_reader::read_file(path, multiline, options, follow, tx);
+        } else {
+            self.status_message = format!("Loading and merging: {}", paths.join(", "));
+            file_reader::read_files(paths, multiline, options, tx);
+        }
     }
 
     fn start_ssh(&mut self, config: ssh_reader::SshConfig) {
@@ -149,6 +223,8 @@ impl JlogApp {
         self.is_loading = true;
         self.is_connected = false;
         self.status_message = format!("Connecting to {}...", config.host);
+        self.source_warnings = rules_warning(&config.parse_options).into_iter().collect();
+        self.log_viewer.set_source_zone(config.parse_options.clock.tz.clone());
 
         let (tx, rx) = unbounded();
         let (cmd_tx, cmd_rx) = unbounded();
@@ -157,6 +233,82 @@ impl JlogApp {
         ssh_reader::start_ssh(config, tx, cmd_rx);
     }
 
+    fn start_stdin(&mut self) {
+        self.reset_state();
+        self.current_host = "stdin".to_string();
+        self.is_loading = true;
+        self.is_reading_stdin = true;
+        self.status_message = "Reading stdin - streaming...".to_string();
+
+        // The Open File dialog's settings (or --year / --tz / --format) apply, as for files.
+        // Invalid ones shouldn't stop the stream, which can't be read again
+        let (multiline, multiline_error) = match self.open_file_dialog.multiline_rules() {
+            Ok(rules) => (rules, None),
+            Err(e) => (MultilineRules::default(), Some(e)),
+        };
+        let (options, options_error) = match self.open_file_dialog.parse_options() {
+            Ok(options) => (options, None),
+            Err(e) => (ParseOptions::new(SyslogClock::default(), "").unwrap_or_default(), Some(e)),
+        };
+        self.source_warnings = [multiline_error, options_error, rules_warning(&options)].into_iter().flatten().collect();
+        self.log_viewer.set_source_zone(options.clock.tz.clone());
+
+        let (tx, rx) = unbounded();
+        let (cmd_tx, cmd_rx) = unbounded();
+        self.bg_receiver = Some(rx);
+        self.bg_cmd_sender = Some(cmd_tx);
+        stdin_reader::start_stdin(multiline, options, tx, cmd_rx);
+    }
+
+    /// Read the current files or SSH source again, forcing `format` (empty = detect).
+    fn reparse_as(&mut self, format: &str) {
+        if self.current_host == "local" {
+            self.open_file_dialog.format = format.to_string();
+            let paths = self.last_paths.clone();
+            self.load_files(paths);
+        } else if let Some(mut config) = self.last_ssh_config.clone() {
+            match ParseOptions::new(config.parse_options.clock.clone(), format) {
+                Ok(options) => {
+                    config.parse_options = options;
+                    self.start_ssh(config);
+                }
+                Err(e) => self.status_message = format!("Error: {}", e),
+            }
+        }
+    }
+
+    /// Write priority_rules.json and read the current files or SSH source again, so their
+    /// entries get the new rules.
+    fn save_priority_rules(&mut self, rules: &[RuleDef]) {
+        if let Err(e) = priority_rules::save_defs(rules) {
+            self.status_message = format!("Error: {}", e);
+            return;
+        }
+        let format = if self.current_host == "local" {
+            Some(self.open_file_dialog.format.clone()).filter(|_| !self.last_paths.is_empty())
+        } else if self.current_host == "stdin" {
+            None
+        } else {
+            self.last_ssh_config.as_ref().map(|config| {
+                let options = &config.parse_options;
+                options.forced_format().map(|f| options.format_name(f)).unwrap_or_default()
+            })
+        };
+        match format {
+            Some(format) => self.reparse_as(&format),
+            None => self.status_message = "Priority rules saved".to_string(),
+        }
+    }
+
+    fn stop_following(&mut self) {
+        if let Some(ref sender) = self.bg_cmd_sender {
+            let _ = sender.send(BackgroundCommand::Cancel);
+        }
+        self.bg_cmd_sender = None;
+        self.is_following = false;
+        self.status_message = format!("Stopped following - {} entries loaded", self.log_store.entries.len());
+    }
+
     fn disconnect(&mut self) {
         if let Some(ref sender) = self.bg_cmd_sender {
             let _ = sender.send(BackgroundCommand::Disconnect);
@@ -177,7 +329,12 @@ impl JlogApp {
         self.bg_cmd_sender = None;
         self.is_loading = false;
         self.is_connected = false;
+        self.is_reading_stdin = false;
+        self.is_following = false;
         self.total_lines = 0;
+        self.detected_format = None;
+        self.parse_failures = None;
+        self.source_warnings.clear();
         self.filter_bar = FilterBar::default();
         self.filter = FilterCriteria::default();
     }
@@ -196,6 +353,15 @@ impl JlogApp {
                 Ok(msg) => match msg {
                     BackgroundMessage::Entry(entry) => {
                         self.log_store.services.insert(entry.service.clone());
+                        if let Some(facility) = entry.facility {
+                            self.log_store.facilities.insert(facility);
+                        }
+                        if let Some(ref source) = entry.source {
+                            self.log_store.sources.insert(source.to_string());
+                        }
+                        if let Some(status) = entry.fields.get_all(STATUS_FIELD).next().and_then(|s| s.parse().ok()) {
+                            self.log_store.statuses.insert(status);
+                        }
                         self.log_store.entries.push(entry);
                         new_entries = true;
                     }
@@ -216,19 +382,45 @@ impl JlogApp {
                     BackgroundMessage::Completed { total_lines, entries } => {
                         self.is_loading = false;
                         self.total_lines = total_lines;
-                        self.status_message = format!(
-                            "Loaded {} entries from {} lines",
-                            entries, total_lines
-                        );
+                        let loaded = format!("Loaded {} entries from {} lines", entries, total_lines);
+                        // A read error is sent just before; keep it visible
+                        if self.status_message.starts_with("Error") {
+                            self.status_message = format!("{} - {}", self.status_message, loaded);
+                        } else {
+                            self.status_message = loaded;
+                        }
+                        if self.is_following {
+                            self.status_message.push_str(" - following for new lines");
+                        }
+                    }
+                    BackgroundMessage::FormatDetected { format, confidence } => {
+                        self.detected_format = Some((format, confidence));
+                    }
+                    BackgroundMessage::ParseFailures { count, samples } => {
+                        self.parse_failures = Some((count, samples));
                     }
                     BackgroundMessage::Error(e) => {
                         self.is_loading = false;
+                        self.is_following = false;
                         self.status_message = format!("Error: {}", e);
                     }
                     BackgroundMessage::SshConnected => {
                         self.is_connected = true;
                         self.status_message = "SSH connected - streaming...".to_string();
                     }
+                    BackgroundMessage::StdinClosed => {
+                        self.is_reading_stdin = false;
+                        self.is_loading = false;
+                        if self.save_settings.auto_save && !self.log_store.entries.is_empty() {
+                            self.save_now();
+                        }
+                        if !self.status_message.starts_with("Error") && !self.status_message.starts_with("Saved") {
+                            self.status_message = format!(
+                                "End of input - {} entries loaded",
+                                self.log_store.entries.len()
+                            );
+                        }
+                    }
                     BackgroundMessage::SshDisconnected => {
                         self.is_connected = false;
                         self.is_loading = false;
@@ -301,6 +493,16 @@ impl JlogApp {
         }
     }
 
+    fn sort_by_time(&mut self) {
+        let old_to_new = self.log_store.sort_by_time();
+        self.bookmarks = self.bookmarks.iter().map(|&i| old_to_new[i]).collect();
+        self.log_viewer.selected_entry = self.log_viewer.selected_entry.map(|i| old_to_new[i]);
+        self.apply_filter();
+        if let Some(idx) = self.log_viewer.selected_entry {
+            self.log_viewer.scroll_to_row = self.filtered_indices.iter().position(|&i| i == idx);
+        }
+    }
+
     fn apply_filter(&mut self) {
         self.filtered_indices.clear();
         for (i, entry) in self.log_store.entries.iter().enumerate() {
@@ -333,14 +535,22 @@ impl eframe::App for JlogApp {
         ctx.request_repaint();
 
         // Load file from CLI argument on first frame
-        if let Some(path) = self.pending_file.take() {
-            self.load_file(path);
+        // Read stdin from CLI argument `-` on first frame
+        if self.pending_stdin {
+            self.pending_stdin = false;
+            self.start_stdin();
+        }
+
+        if !self.pending_files.is_empty() {
+            let paths = std::mem::take(&mut self.pending_files);
+            self.load_files(paths);
         }
 
         // Connect to SSH profile from CLI argument on first frame
         if let Some(name) = self.pending_ssh_profile.take() {
-            if let Some(config) = crate::ui::connection_dialog::config_for_profile(&name) {
+            if let Some((config, warning)) = crate::ui::connection_dialog::config_for_profile(&name) {
                 self.start_ssh(config);
+                self.source_warnings.extend(warning);
             } else {
                 self.status_message = format!("SSH profile '{}' not found", name);
             }
@@ -350,8 +560,8 @@ impl eframe::App for JlogApp {
         self.process_messages();
 
         // Dialogs
-        if let Some(path) = self.open_file_dialog.show(ctx) {
-            self.load_file(path);
+        if let Some(paths) = self.open_file_dialog.show(ctx) {
+            self.load_files(paths);
         }
         if let Some(config) = self.connection_dialog.show(ctx) {
             self.start_ssh(config);
@@ -360,6 +570,9 @@ impl eframe::App for JlogApp {
             save_settings_to_disk(&new_settings);
             self.save_settings = new_settings;
         }
+        if let Some(rules) = self.priority_rules_dialog.show(ctx, &self.log_store.entries) {
+            self.save_priority_rules(&rules);
+        }
 
         // Help window
         if self.show_help {
@@ -506,6 +719,11 @@ impl eframe::App for JlogApp {
                         self.save_settings_dialog.load_from(&self.save_settings);
                         self.save_settings_dialog.open = true;
                     }
+                    if ui.button("Priority Rules...").clicked() {
+                        ui.close_menu();
+                        self.priority_rules_dialog.load();
+                        self.priority_rules_dialog.open = true;
+                    }
                     ui.separator();
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -540,6 +758,54 @@ impl eframe::App for JlogApp {
 
                 ui.menu_button("View", |ui| {
                     ui.checkbox(&mut self.log_viewer.auto_scroll, "Auto-scroll");
+                    ui.menu_button("Time Format", |ui| {
+                        for format in TimeFormat::ALL {
+                            if ui.radio_value(&mut self.log_viewer.time_format, format, format.label()).clicked() {
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Time Zone", |ui| {
+                        let current = self.log_viewer.display_zone().clone();
+                        let mut choice = None;
+                        for (zone, label) in [
+                            (DisplayZone::Utc, "UTC"),
+                            (DisplayZone::Local, "Local"),
+                            (DisplayZone::Source, "Source (zone set in Open File / SSH profile)"),
+                        ] {
+                            if ui.radio(current == zone, label).clicked() {
+                                choice = Some(zone);
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            let other = ui.radio(matches!(current, DisplayZone::Named(_)), "Other:").clicked();
+                            let resp = ui.add(
+                                egui::TextEdit::singleline(&mut self.zone_text)
+                                    .desired_width(160.0)
+                                    .hint_text("e.g. Europe/Berlin"),
+                            );
+                            let enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            if ui.button("Apply").clicked() || enter || other {
+                                choice = Some(DisplayZone::Named(self.zone_text.trim().to_string()));
+                            }
+                        });
+                        if let Some(ref err) = self.zone_error {
+                            ui.colored_label(egui::Color32::RED, err);
+                        }
+                        if let Some(zone) = choice {
+                            match self.log_viewer.set_display_zone(zone) {
+                                Ok(()) => {
+                                    self.zone_error = None;
+                                    ui.close_menu();
+                                }
+                                Err(e) => self.zone_error = Some(e),
+                            }
+                        }
+                    });
+                    if ui.button("Sort by Time").clicked() {
+                        self.sort_by_time();
+                        ui.close_menu();
+                    }
                     ui.separator();
                     let bookmark_label = format!("Bookmarks ({})...", self.bookmarks.len());
                     if ui.button(bookmark_label).clicked() {
@@ -560,7 +826,16 @@ impl eframe::App for JlogApp {
         // Filter bar panel
         egui::TopBottomPanel::top("filter_bar").show(ctx, |ui| {
             let services = self.log_store.service_names();
-            if self.filter_bar.show(ui, &services, &mut self.filter) {
+            let facilities = self.log_store.facility_codes();
+            let sources = self.log_store.source_names();
+            let statuses = self.log_store.status_codes();
+            // The time boxes are typed in the display zone
+            if self.filter_bar.time_zone != *self.log_viewer.time_zone() {
+                self.filter_bar.time_zone = self.log_viewer.time_zone().clone();
+                self.filter_bar.apply_to_filter(&mut self.filter);
+                self.apply_filter();
+            }
+            if self.filter_bar.show(ui, &services, &facilities, &sources, &statuses, &mut self.filter) {
                 self.apply_filter();
             }
         });
@@ -585,6 +860,12 @@ impl eframe::App for JlogApp {
             }
         }
 
+        // Handle "filter on this field" request from Row Detail
+        if let Some((key, value)) = self.log_viewer.filter_field_requested.take() {
+            self.filter_bar.add_field_filter(&key, &value, &mut self.filter);
+            self.apply_filter();
+        }
+
         // Find bar shortcuts (only when active)
         let mut find_next = false;
         let mut find_prev = false;
@@ -717,10 +998,19 @@ impl eframe::App for JlogApp {
         let mut reconnect_action = false;
         let mut disconnect_action = false;
         let mut connect_action = false;
+        let mut stop_following_action = false;
+        let mut reparse_format: Option<String> = None;
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if self.is_connected {
                     ui.colored_label(egui::Color32::GREEN, "\u{25CF} Connected");
+                } else if self.is_reading_stdin {
+                    ui.colored_label(egui::Color32::GREEN, "\u{25CF} stdin");
+                } else if self.is_following {
+                    ui.colored_label(egui::Color32::GREEN, "\u{25CF} Following");
+                    if ui.small_button("Stop").clicked() {
+                        stop_following_action = true;
+                    }
                 } else if self.is_loading {
                     ui.colored_label(egui::Color32::YELLOW, "\u{25CF} Loading");
                 } else {
@@ -746,6 +1036,62 @@ impl eframe::App for JlogApp {
 
                 ui.separator();
                 ui.label(&self.status_message);
+                if let Some((ref format, confidence)) = self.detected_format {
+                    ui.separator();
+                    let text = match confidence {
+                        Some(confidence) => format!("Format: {} ({:.0}%)", format, confidence * 100.0),
+                        None => format!("Format: {}", format),
+                    };
+                    // stdin can't be read again
+                    if self.current_host == "stdin" {
+                        ui.label(text);
+                    } else {
+                        let menu = ui.menu_button(text, |ui| {
+                            ui.label("Re-parse as:");
+                            if ui.button("Auto-detect").clicked() {
+                                reparse_format = Some(String::new());
+                                ui.close_menu();
+                            }
+                            for format in LineFormat::BUILTIN {
+                                if ui.button(format.label()).clicked() {
+                                    reparse_format = Some(format.id().to_string());
+                                    ui.close_menu();
+                                }
+                            }
+                            let names = self.format_menu_names.get_or_insert_with(custom::format_names);
+                            for name in names.iter() {
+                                if ui.button(name).clicked() {
+                                    reparse_format = Some(name.clone());
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if menu.inner.is_none() {
+                            self.format_menu_names = None;
+                        }
+                    }
+                }
+                if let Some((count, ref samples)) = self.parse_failures {
+                    ui.separator();
+                    let plural = if count == 1 { "" } else { "s" };
+                    let mut hint = String::from("Lines no format matched, shown as raw entries:");
+                    for sample in samples {
+                        hint.push('\n');
+                        hint.push_str(sample);
+                    }
+                    if count > samples.len() {
+                        hint.push_str(&format!("\n... and {} more", count - samples.len()));
+                    }
+                    ui.colored_label(egui::Color32::YELLOW, format!("{} unparsed line{}", count, plural))
+                        .on_hover_text(hint);
+                }
+                if !self.source_warnings.is_empty() {
+                    ui.separator();
+                    let count = self.source_warnings.len();
+                    let plural = if count == 1 { "" } else { "s" };
+                    ui.colored_label(egui::Color32::YELLOW, format!("{} warning{}", count, plural))
+                        .on_hover_text(self.source_warnings.join("\n"));
+                }
                 ui.separator();
                 ui.label(format!(
                     "Showing {} / {} entries",
@@ -756,6 +1102,9 @@ impl eframe::App for JlogApp {
         });
 
         // Handle status bar button actions (outside borrow of ui)
+        if stop_following_action {
+            self.stop_following();
+        }
         if disconnect_action {
             self.disconnect();
         }
@@ -767,6 +1116,9 @@ impl eframe::App for JlogApp {
         if connect_action {
             self.connection_dialog.open = true;
         }
+        if let Some(format) = reparse_format {
+            self.reparse_as(&format);
+        }
 
         // Handle "Show in Context" request (before rendering panels so data is ready this frame)
         if self.log_viewer.show_in_context_requested {
@@ -852,12 +1204,12 @@ impl eframe::App for JlogApp {
                                                 .color(egui::Color32::from_rgb(255, 200, 50)),
                                         ));
                                         ui.add_sized([160.0, 18.0], egui::Label::new(
-                                            egui::RichText::new(&entry.timestamp)
+                                            egui::RichText::new(format_entry_time(entry, TimeFormat::Seconds, None, self.log_viewer.time_zone()))
                                                 .monospace()
                                                 .color(egui::Color32::from_rgb(180, 180, 180)),
                                         ));
                                         ui.add_sized([55.0, 18.0], egui::Label::new(
-                                            egui::RichText::new(priority_label(entry.priority))
+                                            egui::RichText::new(entry_priority_label(entry))
                                                 .monospace()
                                                 .color(priority_color(entry.priority)),
                                         ));
@@ -866,15 +1218,16 @@ impl eframe::App for JlogApp {
                                                 .monospace()
                                                 .color(egui::Color32::from_rgb(130, 200, 255)),
                                         ));
-                                        // Truncate message safely at char boundary
+                                        // Truncate message (first line only) safely at char boundary
                                         let msg = {
                                             let limit = 80;
-                                            if entry.message.len() > limit {
-                                                let end = entry.message.char_indices()
+                                            let first_line = entry.message.lines().next().unwrap_or("");
+                                            if first_line.len() > limit || first_line.len() < entry.message.len() {
+                                                let end = first_line.char_indices()
                                                     .map(|(i, _)| i)
                                                     .nth(limit)
-                                                    .unwrap_or(entry.message.len());
-                                                format!("{}…", &entry.message[..end])
+                                                    .unwrap_or(first_line.len());
+                                                format!("{}…", &first_line[..end])
                                             } else {
                                                 entry.message.clone()
                                             }
@@ -939,3 +1292,8 @@ impl eframe::App for JlogApp {
         });
     }
 }
+
+/// Status bar note for a priority_rules.json that couldn't be used.
+fn rules_warning(options: &ParseOptions) -> Option<String> {
+    options.rules_error().map(|e| format!("{} - using the built-in priority rules (File > Priority Rules)", e))
+}
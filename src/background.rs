@@ -5,8 +5,17 @@ pub enum BackgroundMessage {
     Progress { lines: usize, percent: f32 },
     Completed { total_lines: usize, entries: usize },
     Error(String),
+    /// Format a source was read as: a line format id, a user format name, "journal-file"
+    /// or "journal-export". `confidence` is the share of sniffed lines it matched, None
+    /// when the format was forced or is certain from the file header.
+    FormatDetected { format: String, confidence: Option<f32> },
+    /// Lines that matched no format so far (kept as raw entries), with the first few as
+    /// "<line number>: <text>" samples, prefixed with "<file>:" when files are merged.
+    ParseFailures { count: usize, samples: Vec<String> },
     SshConnected,
     SshDisconnected,
+    /// End of input for `jlog -`.
+    StdinClosed,
 }
 
 pub enum BackgroundCommand {
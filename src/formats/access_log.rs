@@ -0,0 +1,201 @@
+//! Web server access logs in the NCSA common and combined formats nginx and Apache write
+//! by default:
+//!
+//! `203.0.113.7 - alice [16/Oct/2026:10:00:00 +0200] "GET /api/items?id=3 HTTP/1.1" 502 157 "https://example.com/" "curl/8.5.0" 0.254`
+//!
+//! The referer / user agent pair is optional (common format), as is Apache's
+//! `vhost_combined` `host:port` prefix. Anything after the user agent is scanned for the
+//! request time (nginx `$request_time`, or an `rt=` / `request_time=` pair).
+
+use std::collections::BTreeMap;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use crate::analyzer::STATUS_FIELD;
+use crate::journalctl::{JournalEntry, JournalValue};
+
+static ACCESS_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r#"^(?:(\S+:\d+) )?(\S+) (\S+) (\S+) \[([^\]]+)\] "((?:[^"\\]|\\.)*)" (\d{3}) (\d+|-)"#,
+        r#"(?: "((?:[^"\\]|\\.)*)" "((?:[^"\\]|\\.)*)")?(.*)$"#,
+    ))
+    .unwrap()
+});
+
+/// Parse one access log line, or None if it isn't one.
+pub fn parse_access_line(line: &str) -> Option<JournalEntry> {
+    let caps = ACCESS_LINE_REGEX.captures(line)?;
+    let realtime_us = chrono::DateTime::parse_from_str(&caps[5], "%d/%b/%Y:%H:%M:%S %z")
+        .ok()
+        .map(|dt| dt.timestamp_micros());
+    let request = unescape(&caps[6]);
+    let status: u16 = caps[7].parse().ok()?;
+
+    let mut fields = vec![("CLIENT_IP".to_string(), caps[2].to_string())];
+    let mut push = |key: &str, value: &str| {
+        if !value.is_empty() && value != "-" {
+            fields.push((key.to_string(), value.to_string()));
+        }
+    };
+    push("REMOTE_USER", &caps[4]);
+    // A request line too mangled to split (scanners, TLS on a plain port) is kept whole
+    match request.split(' ').collect::<Vec<_>>()[..] {
+        [method, path, protocol] => {
+            push("HTTP_METHOD", method);
+            push("HTTP_PATH", path);
+            push("HTTP_PROTOCOL", protocol);
+        }
+        _ => push("HTTP_REQUEST", &request),
+    }
+    push(STATUS_FIELD, &caps[7]);
+    push("HTTP_BYTES", &caps[8]);
+    if let Some(referer) = caps.get(9) {
+        push("HTTP_REFERER", &unescape(referer.as_str()));
+    }
+    if let Some(agent) = caps.get(10) {
+        push("HTTP_USER_AGENT", &unescape(agent.as_str()));
+    }
+    if let Some(request_time) = request_time(&caps[11]) {
+        push("HTTP_REQUEST_TIME", request_time);
+    }
+    if let Some(vhost) = caps.get(1) {
+        push("HTTP_VHOST", vhost.as_str());
+    }
+
+    Some(JournalEntry {
+        realtime_timestamp: realtime_us.map(|t| t.to_string()),
+        priority: Some(status_priority(status).to_string()),
+        facility: None,
+        syslog_identifier: Some("http".to_string()),
+        systemd_unit: None,
+        message: JournalValue::Text(format!("{} {} {}", request, status, &caps[8])),
+        fields,
+        priority_inferred: false,
+        extra: BTreeMap::new(),
+    })
+}
+
+/// 5xx is a server error, 4xx a client one worth a look; everything else is routine.
+fn status_priority(status: u16) -> u8 {
+    match status {
+        500..=599 => 3,
+        400..=499 => 4,
+        _ => 6,
+    }
+}
+
+/// Request time from the text after the user agent: a bare number (nginx `$request_time`
+/// appended to the combined format) or an `rt=` / `request_time=` pair.
+fn request_time(rest: &str) -> Option<&str> {
+    rest.split_whitespace()
+        .map(|token| token.trim_matches('"'))
+        .find_map(|token| match token.split_once('=') {
+            Some(("rt" | "request_time", value)) => Some(value),
+            Some(_) => None,
+            None => token.parse::<f64>().is_ok().then_some(token),
+        })
+}
+
+/// nginx and Apache escape `"` and `\` (and other bytes as `\xHH`) inside quoted fields.
+/// Control bytes stay escaped so they remain visible.
+fn unescape(text: &str) -> String {
+    if !text.contains('\\') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) if byte.is_ascii_graphic() || byte == b' ' => out.push(byte as char),
+                    _ => {
+                        out.push_str("\\x");
+                        out.push_str(&hex);
+                    }
+                }
+            }
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<'a>(entry: &'a JournalEntry, key: &str) -> Option<&'a str> {
+        entry.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn combined_line_with_request_time() {
+        let line = r#"203.0.113.7 - alice [16/Oct/2026:10:00:00 +0200] "GET /api/items?id=3 HTTP/1.1" 502 157 "https://example.com/" "curl/8.5.0" 0.254"#;
+        let entry = parse_access_line(line).unwrap();
+        assert_eq!(entry.realtime_timestamp.as_deref(), Some("1792137600000000"));
+        assert_eq!(entry.priority.as_deref(), Some("3"));
+        assert_eq!(entry.message.text().as_deref(), Some("GET /api/items?id=3 HTTP/1.1 502 157"));
+        assert_eq!(field(&entry, "CLIENT_IP"), Some("203.0.113.7"));
+        assert_eq!(field(&entry, "REMOTE_USER"), Some("alice"));
+        assert_eq!(field(&entry, "HTTP_METHOD"), Some("GET"));
+        assert_eq!(field(&entry, "HTTP_PATH"), Some("/api/items?id=3"));
+        assert_eq!(field(&entry, STATUS_FIELD), Some("502"));
+        assert_eq!(field(&entry, "HTTP_REFERER"), Some("https://example.com/"));
+        assert_eq!(field(&entry, "HTTP_USER_AGENT"), Some("curl/8.5.0"));
+        assert_eq!(field(&entry, "HTTP_REQUEST_TIME"), Some("0.254"));
+        assert_eq!(field(&entry, "HTTP_VHOST"), None);
+    }
+
+    #[test]
+    fn common_line_with_vhost_and_no_body() {
+        let line = r#"www.example.com:443 198.51.100.2 - - [16/Oct/2026:08:00:00 +0000] "HEAD / HTTP/1.0" 404 -"#;
+        let entry = parse_access_line(line).unwrap();
+        assert_eq!(entry.priority.as_deref(), Some("4"));
+        assert_eq!(field(&entry, "HTTP_VHOST"), Some("www.example.com:443"));
+        assert_eq!(field(&entry, "CLIENT_IP"), Some("198.51.100.2"));
+        // "-" means absent
+        assert_eq!(field(&entry, "REMOTE_USER"), None);
+        assert_eq!(field(&entry, "HTTP_BYTES"), None);
+        assert_eq!(field(&entry, "HTTP_USER_AGENT"), None);
+    }
+
+    #[test]
+    fn mangled_request_is_kept_whole() {
+        let line = r#"192.0.2.9 - - [16/Oct/2026:08:00:00 +0000] "\x16\x03\x01 \"x\"" 400 0 "-" "-""#;
+        let entry = parse_access_line(line).unwrap();
+        assert_eq!(field(&entry, "HTTP_REQUEST"), Some(r#"\x16\x03\x01 "x""#));
+        assert_eq!(field(&entry, "HTTP_METHOD"), None);
+        assert_eq!(field(&entry, "HTTP_REFERER"), None);
+    }
+
+    #[test]
+    fn other_lines_are_not_access_lines() {
+        assert!(parse_access_line("Oct 16 10:00:00 host sshd[1]: Accepted publickey").is_none());
+        assert!(parse_access_line(r#"1.2.3.4 - - [16/Oct/2026:10:00:00 +0000] "GET / HTTP/1.1" ok 12"#).is_none());
+        assert!(parse_access_line(r#"1.2.3.4 - - [16/Oct/2026:10:00:00 +0000] "GET / HTTP/1.1 200 12"#).is_none());
+    }
+
+    #[test]
+    fn request_time_forms() {
+        assert_eq!(request_time(" 0.254"), Some("0.254"));
+        assert_eq!(request_time(r#" "0.5""#), Some("0.5"));
+        assert_eq!(request_time(" upstream=10.0.0.1 rt=1.002 uct=0.001"), Some("1.002"));
+        assert_eq!(request_time(" request_time=0.010"), Some("0.010"));
+        assert_eq!(request_time(" upstream=10.0.0.1 -"), None);
+        assert_eq!(request_time(""), None);
+    }
+
+    #[test]
+    fn unescape_keeps_control_bytes_visible() {
+        assert_eq!(unescape("plain"), "plain");
+        assert_eq!(unescape(r#"say \"hi\" \\ \x41\x20b"#), r#"say "hi" \ A b"#);
+        assert_eq!(unescape(r"\x1b[31m \x0a"), r"\x1b[31m \x0a");
+        assert_eq!(unescape(r"trailing \"), r"trailing \");
+    }
+}
@@ -0,0 +1,372 @@
+//! Linux audit logs (`/var/log/audit/audit.log`):
+//!
+//! `type=SYSCALL msg=audit(1697040000.123:4567): arch=c000003e syscall=59 success=yes ... key="exec"`
+//!
+//! One event is written as several records sharing the `audit(<time>:<serial>)` stamp
+//! (SYSCALL, EXECVE, CWD, PATH, PROCTITLE, ..., often closed by an EOE record). They are
+//! grouped into one entry whose message is the command line followed by the records, with
+//! hex-encoded values such as `proctitle` decoded. Each record's values also become
+//! `<record type>.<name>` fields (`syscall.exe`, `path.name`); like any `key=value` message,
+//! the pairs are filterable unprefixed too (`key=exec_watch`).
+
+use std::collections::BTreeMap;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use crate::journalctl::{JournalEntry, JournalValue, parse_decimal_micros};
+
+/// "[node=<host> ]type=<TYPE> msg=audit(<seconds>.<millis>:<serial>): <body>"
+static AUDIT_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:node=(\S+) )?type=(\S+) msg=audit\((\d+\.\d+):(\d+)\):\s*(.*)$").unwrap()
+});
+
+/// Values auditd hex-encodes (when they hold spaces, quotes or control characters) instead
+/// of quoting. EXECVE arguments (`a0`, `a1`, ...) are too, but not SYSCALL's `a0`-`a3`.
+const ENCODED_KEYS: &[&str] = &["proctitle", "name", "cwd", "comm", "exe", "path", "cmd", "acct", "data", "key"];
+
+/// One line of audit.log.
+pub struct AuditRecord {
+    node: Option<String>,
+    kind: String,
+    time_us: Option<i64>,
+    serial: String,
+    /// The record with hex-encoded values decoded
+    body: String,
+    fields: Vec<(String, String)>,
+}
+
+/// Parse one record, or None if the line isn't one.
+pub fn parse_record(line: &str) -> Option<AuditRecord> {
+    let caps = AUDIT_LINE_REGEX.captures(line)?;
+    let mut record = AuditRecord {
+        node: caps.get(1).map(|m| m.as_str().to_string()),
+        kind: caps[2].to_string(),
+        time_us: parse_decimal_micros(&caps[3]),
+        serial: caps[4].to_string(),
+        body: String::new(),
+        fields: Vec::new(),
+    };
+    // ENRICHED logs append the translated values after a GS character
+    record.add_tokens(&caps[5].replace('\u{1d}', " "));
+    Some(record)
+}
+
+impl AuditRecord {
+    fn add_tokens(&mut self, text: &str) {
+        for token in split_tokens(text) {
+            let (key, value) = match token {
+                Token::Word(word) => {
+                    self.push_body(word);
+                    continue;
+                }
+                // USER_* records nest the user-space message: msg='op=login acct="bob" res=success'
+                Token::Nested(inner) => {
+                    self.add_tokens(inner);
+                    continue;
+                }
+                Token::Quoted(key, value) => (key, value.to_string()),
+                Token::Bare(key, value) if is_encoded(&self.kind, key) => {
+                    (key, decode_hex(value).unwrap_or_else(|| value.to_string()))
+                }
+                Token::Bare(key, value) => (key, value.to_string()),
+            };
+            if value.is_empty() || value.contains(char::is_whitespace) {
+                self.push_body(&format!("{}=\"{}\"", key, value));
+            } else {
+                self.push_body(&format!("{}={}", key, value));
+            }
+            if value != "(null)" && value != "?" {
+                self.fields.push((key.to_string(), value));
+            }
+        }
+    }
+
+    fn push_body(&mut self, text: &str) {
+        if !self.body.is_empty() {
+            self.body.push(' ');
+        }
+        self.body.push_str(text);
+    }
+}
+
+enum Token<'a> {
+    Word(&'a str),
+    Quoted(&'a str, &'a str),
+    Bare(&'a str, &'a str),
+    /// A single-quoted value holding more pairs
+    Nested(&'a str),
+}
+
+/// Split `key=value key="quoted value" msg='nested'` and the free words between them.
+fn split_tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let Some(eq) = rest[..word_end].find('=').filter(|&eq| eq > 0) else {
+            tokens.push(Token::Word(&rest[..word_end]));
+            rest = rest[word_end..].trim_start();
+            continue;
+        };
+        let key = &rest[..eq];
+        let after = &rest[eq + 1..];
+        let remainder = match after.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = after[1..].find(quote).map_or(after.len(), |end| end + 1);
+                let inner = &after[1..end];
+                tokens.push(if quote == '"' { Token::Quoted(key, inner) } else { Token::Nested(inner) });
+                after.get(end + 1..).unwrap_or("")
+            }
+            _ => {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                tokens.push(Token::Bare(key, &after[..end]));
+                &after[end..]
+            }
+        };
+        rest = remainder.trim_start();
+    }
+    tokens
+}
+
+fn is_encoded(kind: &str, key: &str) -> bool {
+    ENCODED_KEYS.contains(&key)
+        || (kind == "EXECVE" && key.len() > 1 && key.starts_with('a') && key[1..].bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Decode an unquoted hex value. NULs (between proctitle's arguments) become spaces.
+fn decode_hex(text: &str) -> Option<String> {
+    if text.is_empty() || !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let bytes: Vec<u8> = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map(|b| if b == 0 { b' ' } else { b }))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    Some(String::from_utf8_lossy(&bytes).trim_end().to_string())
+}
+
+/// Events still waiting for records before the oldest is taken as complete. auditd can
+/// interleave the records of events from different CPUs, but not many at once.
+const MAX_OPEN_EVENTS: usize = 4;
+
+/// Groups records with the same serial into events.
+#[derive(Default)]
+pub struct AuditReader {
+    /// Events being collected, oldest first, with the line number of each one's first record
+    events: Vec<(Vec<AuditRecord>, usize)>,
+}
+
+impl AuditReader {
+    /// Add a record. Returns an event, with its first line number, when this record is its
+    /// closing EOE, or when it starts an event and the oldest open one has to be given up
+    /// on (records without an EOE, like USER_LOGIN, are only finished that way).
+    pub fn push(&mut self, record: AuditRecord, line_num: usize) -> Option<(JournalEntry, usize)> {
+        let open = self.events.iter().position(|(records, _)| records[0].serial == record.serial);
+        if record.kind == "EOE" {
+            // Closes its event; nothing else to show
+            return open.map(|i| self.close(i));
+        }
+        match open {
+            Some(i) => {
+                self.events[i].0.push(record);
+                None
+            }
+            None => {
+                self.events.push((vec![record], line_num));
+                (self.events.len() > MAX_OPEN_EVENTS).then(|| self.close(0))
+            }
+        }
+    }
+
+    /// The oldest event still being collected, if any. Called until it returns None at the
+    /// end of the input.
+    pub fn finish(&mut self) -> Option<(JournalEntry, usize)> {
+        (!self.events.is_empty()).then(|| self.close(0))
+    }
+
+    fn close(&mut self, index: usize) -> (JournalEntry, usize) {
+        let (records, first_line) = self.events.remove(index);
+        (event_entry(&records), first_line)
+    }
+}
+
+/// A single record as an entry of its own, for audit lines outside an audit.log.
+pub fn parse_audit_line(line: &str) -> Option<JournalEntry> {
+    parse_record(line).map(|record| event_entry(&[record]))
+}
+
+fn event_entry(records: &[AuditRecord]) -> JournalEntry {
+    let first = &records[0];
+    let mut fields = vec![("_AUDIT_ID".to_string(), first.serial.clone())];
+    if let Some(ref node) = first.node {
+        fields.push(("_HOSTNAME".to_string(), node.clone()));
+    }
+    for record in records {
+        fields.push(("_AUDIT_TYPE_NAME".to_string(), record.kind.clone()));
+        let prefix = record.kind.to_ascii_lowercase();
+        for (key, value) in &record.fields {
+            fields.push((format!("{}.{}", prefix, key), value.clone()));
+        }
+    }
+
+    // The command line, from PROCTITLE or else EXECVE's arguments
+    let command = records.iter()
+        .find(|r| r.kind == "PROCTITLE")
+        .and_then(|r| r.fields.iter().find(|(k, _)| k == "proctitle"))
+        .map(|(_, v)| v.clone())
+        .or_else(|| records.iter().find(|r| r.kind == "EXECVE").map(|r| {
+            r.fields.iter()
+                .filter(|(k, _)| is_encoded("EXECVE", k))
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }));
+    // The records one per line, under the command that ran when there was one
+    let mut lines: Vec<String> = command.into_iter().map(|command| format!("{}: {}", first.kind, command)).collect();
+    lines.extend(records.iter().map(|record| format!("{} {}", record.kind, record.body)));
+    let message = lines.join("\n");
+
+    // Denials, anomalies and failed operations deserve a look
+    let failed = records.iter().any(|r| {
+        r.kind.starts_with("ANOM_")
+            || r.fields.iter().any(|(k, v)| {
+                (k == "success" && v == "no") || (k == "res" && (v == "failed" || v == "0")) || (k == "seresult" && v == "denied")
+            })
+    }) || records.iter().any(|r| r.kind == "AVC" && r.body.contains("denied"));
+
+    JournalEntry {
+        realtime_timestamp: first.time_us.map(|t| t.to_string()),
+        priority: Some(if failed { "4" } else { "6" }.to_string()),
+        facility: None,
+        syslog_identifier: Some("audit".to_string()),
+        systemd_unit: None,
+        message: JournalValue::Text(message),
+        fields,
+        priority_inferred: false,
+        extra: BTreeMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_all(reader: &mut AuditReader, lines: &[&str]) -> Vec<(JournalEntry, usize)> {
+        let mut events: Vec<_> = lines.iter()
+            .enumerate()
+            .filter_map(|(i, line)| reader.push(parse_record(line).unwrap(), i + 1))
+            .collect();
+        events.extend(std::iter::from_fn(|| reader.finish()));
+        events
+    }
+
+    fn audit_ids(entry: &JournalEntry) -> Vec<&str> {
+        entry.fields.iter().filter(|(k, _)| k == "_AUDIT_ID").map(|(_, v)| v.as_str()).collect()
+    }
+
+    #[test]
+    fn interleaved_events_are_grouped_by_serial() {
+        let mut reader = AuditReader::default();
+        let events = push_all(&mut reader, &[
+            "type=SYSCALL msg=audit(1697040000.100:10): syscall=59 success=yes exe=\"/usr/bin/ls\"",
+            "type=SYSCALL msg=audit(1697040000.101:11): syscall=2 success=no exe=\"/usr/bin/cat\"",
+            "type=CWD msg=audit(1697040000.100:10): cwd=\"/root\"",
+            "type=PATH msg=audit(1697040000.101:11): item=0 name=\"/etc/shadow\"",
+            "type=EOE msg=audit(1697040000.101:11): ",
+            "type=EOE msg=audit(1697040000.100:10): ",
+        ]);
+        assert_eq!(events.len(), 2);
+        let (ref denied, first_line) = events[0];
+        assert_eq!(audit_ids(denied), ["11"]);
+        assert_eq!(first_line, 2);
+        assert!(denied.fields.iter().any(|(k, v)| k == "path.name" && v == "/etc/shadow"));
+        assert_eq!(denied.priority.as_deref(), Some("4"));
+        let (ref ls, first_line) = events[1];
+        assert_eq!(audit_ids(ls), ["10"]);
+        assert_eq!(first_line, 1);
+        assert!(ls.fields.iter().any(|(k, v)| k == "cwd.cwd" && v == "/root"));
+    }
+
+    #[test]
+    fn events_without_eoe_are_finished_by_newer_ones() {
+        let mut reader = AuditReader::default();
+        let lines: Vec<String> = (0..=MAX_OPEN_EVENTS)
+            .map(|i| format!("type=USER_LOGIN msg=audit(1697040000.{:03}:{}): pid=1 res=success", i, 100 + i))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let first = reader.push(parse_record(lines[0]).unwrap(), 1);
+        assert!(first.is_none());
+        for (i, line) in lines.iter().enumerate().skip(1).take(MAX_OPEN_EVENTS - 1) {
+            assert!(reader.push(parse_record(line).unwrap(), i + 1).is_none());
+        }
+        let (oldest, _) = reader.push(parse_record(lines[MAX_OPEN_EVENTS]).unwrap(), MAX_OPEN_EVENTS + 1).unwrap();
+        assert_eq!(audit_ids(&oldest), ["100"]);
+        assert_eq!(std::iter::from_fn(|| reader.finish()).count(), MAX_OPEN_EVENTS);
+    }
+
+    fn message(entry: &JournalEntry) -> &str {
+        match entry.message {
+            JournalValue::Text(ref text) => text,
+            _ => panic!("audit message isn't text"),
+        }
+    }
+
+    fn field<'a>(entry: &'a JournalEntry, name: &str) -> Option<&'a str> {
+        entry.fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn exec_event_is_one_entry() {
+        let mut reader = AuditReader::default();
+        let events = push_all(&mut reader, &[
+            r#"type=SYSCALL msg=audit(1697040000.123:4567): arch=c000003e syscall=59 success=yes exit=0 a0=55d0 a1=55e0 a2=55f0 a3=0 items=2 pid=1234 uid=0 comm="ls" exe="/usr/bin/ls" key="exec_watch""#,
+            r#"type=EXECVE msg=audit(1697040000.123:4567): argc=2 a0="ls" a1="-la""#,
+            r#"type=PATH msg=audit(1697040000.123:4567): item=0 name="/usr/bin/ls" inode=1234 nametype=NORMAL"#,
+            "type=PROCTITLE msg=audit(1697040000.123:4567): proctitle=6C73002D6C61",
+            "type=EOE msg=audit(1697040000.123:4567): ",
+        ]);
+        assert_eq!(events.len(), 1);
+        let (ref entry, first_line) = events[0];
+        assert_eq!(first_line, 1);
+        assert_eq!(entry.realtime_timestamp.as_deref(), Some("1697040000123000"));
+        assert_eq!(entry.priority.as_deref(), Some("6"));
+        let lines: Vec<&str> = message(entry).lines().collect();
+        assert_eq!(lines[0], "SYSCALL: ls -la");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].starts_with("PROCTITLE proctitle=\"ls -la\""));
+        assert_eq!(field(entry, "syscall.exe"), Some("/usr/bin/ls"));
+        assert_eq!(field(entry, "execve.a1"), Some("-la"));
+        assert_eq!(field(entry, "path.name"), Some("/usr/bin/ls"));
+        assert_eq!(field(entry, "syscall.key"), Some("exec_watch"));
+        let types: Vec<&str> = entry.fields.iter().filter(|(k, _)| k == "_AUDIT_TYPE_NAME").map(|(_, v)| v.as_str()).collect();
+        assert_eq!(types, ["SYSCALL", "EXECVE", "PATH", "PROCTITLE"]);
+    }
+
+    #[test]
+    fn hex_values_are_decoded() {
+        // "bash\0-c\0echo hi" and a path holding a space
+        let record = parse_record("type=PROCTITLE msg=audit(1697040000.123:1): proctitle=62617368002D63006563686F206869").unwrap();
+        assert_eq!(record.fields, [("proctitle".to_string(), "bash -c echo hi".to_string())]);
+        let record = parse_record("type=PATH msg=audit(1697040000.123:1): item=0 name=2F746D702F6D792066696C65").unwrap();
+        assert_eq!(record.fields[1], ("name".to_string(), "/tmp/my file".to_string()));
+        assert!(record.body.ends_with(r#"name="/tmp/my file""#));
+        // Not hex: kept as is
+        let record = parse_record("type=PATH msg=audit(1697040000.123:1): name=/etc/passwd").unwrap();
+        assert_eq!(record.fields[0].1, "/etc/passwd");
+        // SYSCALL's a0-a3 are plain numbers
+        let record = parse_record("type=SYSCALL msg=audit(1697040000.123:1): a0=414243").unwrap();
+        assert_eq!(record.fields[0].1, "414243");
+    }
+
+    #[test]
+    fn unterminated_quote_takes_the_rest() {
+        let record = parse_record(r#"type=USER_CMD msg=audit(1697040000.123:1): pid=1 cmd="ls -la res=success"#).unwrap();
+        assert_eq!(record.fields, [
+            ("pid".to_string(), "1".to_string()),
+            ("cmd".to_string(), "ls -la res=success".to_string()),
+        ]);
+        let record = parse_record("type=USER_LOGIN msg=audit(1697040000.123:1): pid=1 msg='op=login acct=\"bob").unwrap();
+        assert_eq!(record.fields[1], ("op".to_string(), "login".to_string()));
+        assert_eq!(record.fields[2], ("acct".to_string(), "bob".to_string()));
+    }
+}
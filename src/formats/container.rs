@@ -0,0 +1,320 @@
+//! Container runtime log files: Docker's json-file driver
+//! (`/var/lib/docker/containers/<id>/<id>-json.log`, one `{"log":..,"stream":..,"time":..}`
+//! object per line) and the CRI format containerd and CRI-O write for Kubernetes
+//! (`2026-10-16T10:00:00.123456789Z stdout F message`).
+//!
+//! Both split long lines into partial chunks: Docker leaves the trailing newline off all
+//! but the last chunk, CRI tags them `P` instead of `F`. Chunks are joined per stream.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use crate::journalctl::{JournalEntry, JournalValue};
+
+/// Field holding the stream a line was written to ("stdout" or "stderr").
+pub const STREAM_FIELD: &str = "STREAM";
+
+/// Priority of stderr lines: errors and warnings usually go there, so they are WARN unless
+/// a priority rule finds something more severe.
+pub const STDERR_PRIORITY: u8 = 4;
+
+/// "<RFC 3339 time> <stream> <P|F>[:more tags] <content>"
+static CRI_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2}T\S+) (stdout|stderr) ([PF])(?::\S*)?(?: (.*))?$").unwrap()
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerFormat {
+    Docker,
+    Cri,
+}
+
+impl ContainerFormat {
+    /// Recognise a container log from its first line.
+    pub fn detect(first_line: &str) -> Option<Self> {
+        if parse_docker_line(first_line).is_some() {
+            Some(ContainerFormat::Docker)
+        } else if parse_cri_line(first_line).is_some() {
+            Some(ContainerFormat::Cri)
+        } else {
+            None
+        }
+    }
+}
+
+/// One physical line of a container log.
+struct ContainerLine {
+    time: String,
+    stderr: bool,
+    partial: bool,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct DockerLine {
+    log: String,
+    #[serde(default)]
+    stream: String,
+    #[serde(default)]
+    time: String,
+}
+
+fn parse_docker_line(line: &str) -> Option<ContainerLine> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let docker: DockerLine = serde_json::from_str(line).ok()?;
+    let (text, partial) = match docker.log.strip_suffix('\n') {
+        Some(text) => (text.strip_suffix('\r').unwrap_or(text).to_string(), false),
+        None => (docker.log, true),
+    };
+    Some(ContainerLine {
+        time: docker.time,
+        stderr: docker.stream == "stderr",
+        partial,
+        text,
+    })
+}
+
+fn parse_cri_line(line: &str) -> Option<ContainerLine> {
+    let caps = CRI_LINE_REGEX.captures(line)?;
+    Some(ContainerLine {
+        time: caps[1].to_string(),
+        stderr: &caps[2] == "stderr",
+        partial: &caps[3] == "P",
+        text: caps.get(4).map_or("", |m| m.as_str()).to_string(),
+    })
+}
+
+/// Which container a log file belongs to, worked out from its path.
+#[derive(Clone, Debug, Default)]
+pub struct ContainerInfo {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub namespace: Option<String>,
+    pub pod: Option<String>,
+}
+
+impl ContainerInfo {
+    /// Understands the three usual layouts:
+    /// - `/var/lib/docker/containers/<id>/<id>-json.log` (name read from `config.v2.json`)
+    /// - `/var/log/containers/<pod>_<namespace>_<container>-<id>.log`
+    /// - `/var/log/pods/<namespace>_<pod>_<uid>/<container>/<restart>.log`
+    pub fn from_path(path: &Path) -> Self {
+        let mut info = ContainerInfo::default();
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let dir = path.parent();
+        let dir_name = dir.and_then(Path::file_name).and_then(|n| n.to_str()).unwrap_or("");
+
+        // Rotated files keep the base name: <id>-json.log.1, 0.log.20261016-100000.gz
+        if is_container_id(dir_name) && file_name.starts_with(&format!("{}-json.log", dir_name)) {
+            info.id = Some(dir_name.to_string());
+            info.name = dir.and_then(docker_container_name);
+        } else if let Some(stem) = file_name.split(".log").next()
+            && let Some((rest, id)) = stem.rsplit_once('-')
+            && is_container_id(id)
+            && let [pod, namespace, container] = rest.splitn(3, '_').collect::<Vec<_>>()[..]
+        {
+            info.id = Some(id.to_string());
+            info.name = Some(container.to_string());
+            info.namespace = Some(namespace.to_string());
+            info.pod = Some(pod.to_string());
+        } else if let Some(pod_dir) = dir.and_then(Path::parent).and_then(Path::file_name).and_then(|n| n.to_str())
+            && let [namespace, pod, _uid] = pod_dir.splitn(3, '_').collect::<Vec<_>>()[..]
+            && file_name.starts_with(|c: char| c.is_ascii_digit())
+        {
+            info.name = Some(dir_name.to_string());
+            info.namespace = Some(namespace.to_string());
+            info.pod = Some(pod.to_string());
+        }
+        info
+    }
+}
+
+fn is_container_id(text: &str) -> bool {
+    text.len() == 64 && text.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Container name from Docker's per-container config ("Name": "/web-1").
+fn docker_container_name(dir: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(dir.join("config.v2.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&config).ok()?;
+    let name = value.get("Name")?.as_str()?.trim_start_matches('/');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Result of feeding one line to a `ContainerReader`.
+pub enum Pushed {
+    Entry(Box<JournalEntry>),
+    /// A partial chunk, held until the rest of the line arrives.
+    Partial,
+    /// Not a line of this format.
+    Unparsed,
+}
+
+/// Turns container log lines into entries, joining partial chunks.
+pub struct ContainerReader {
+    format: ContainerFormat,
+    info: ContainerInfo,
+    /// Partial chunks so far, per stream (stdout, stderr).
+    partial: [Option<ContainerLine>; 2],
+}
+
+impl ContainerReader {
+    pub fn new(format: ContainerFormat, info: ContainerInfo) -> Self {
+        Self { format, info, partial: [None, None] }
+    }
+
+    pub fn push(&mut self, line: &str) -> Pushed {
+        let parsed = match self.format {
+            ContainerFormat::Docker => parse_docker_line(line),
+            ContainerFormat::Cri => parse_cri_line(line),
+        };
+        let Some(mut line) = parsed else {
+            return Pushed::Unparsed;
+        };
+
+        // The entry keeps the time of its first chunk
+        let stream = line.stderr as usize;
+        if let Some(mut first) = self.partial[stream].take() {
+            first.text.push_str(&line.text);
+            first.partial = line.partial;
+            line = first;
+        }
+        if line.partial {
+            self.partial[stream] = Some(line);
+            return Pushed::Partial;
+        }
+        Pushed::Entry(Box::new(self.entry(line)))
+    }
+
+    fn entry(&self, line: ContainerLine) -> JournalEntry {
+        let realtime_us = chrono::DateTime::parse_from_rfc3339(&line.time)
+            .ok()
+            .map(|dt| dt.timestamp_micros());
+        let mut fields = vec![
+            (STREAM_FIELD.to_string(), if line.stderr { "stderr" } else { "stdout" }.to_string()),
+        ];
+        if let Some(ref id) = self.info.id {
+            fields.push(("CONTAINER_ID".to_string(), id[..12].to_string()));
+            fields.push(("CONTAINER_ID_FULL".to_string(), id.clone()));
+        }
+        if let Some(ref name) = self.info.name {
+            fields.push(("CONTAINER_NAME".to_string(), name.clone()));
+        }
+        if let Some(ref namespace) = self.info.namespace {
+            fields.push(("K8S_NAMESPACE".to_string(), namespace.clone()));
+        }
+        if let Some(ref pod) = self.info.pod {
+            fields.push(("K8S_POD".to_string(), pod.clone()));
+        }
+
+        let service = self.info.name.clone()
+            .or_else(|| self.info.id.as_ref().map(|id| id[..12].to_string()))
+            .unwrap_or_else(|| "container".to_string());
+
+        JournalEntry {
+            realtime_timestamp: realtime_us.map(|t| t.to_string()),
+            priority: line.stderr.then(|| STDERR_PRIORITY.to_string()),
+            facility: None,
+            syslog_identifier: Some(service),
+            systemd_unit: None,
+            message: JournalValue::Text(line.text),
+            fields,
+            priority_inferred: true,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries the lines complete, as (stream, time, message).
+    fn push_all(reader: &mut ContainerReader, lines: &[&str]) -> Vec<(String, String, String)> {
+        lines.iter()
+            .filter_map(|line| match reader.push(line) {
+                Pushed::Entry(entry) => Some(entry),
+                Pushed::Partial => None,
+                Pushed::Unparsed => panic!("unparsed: {}", line),
+            })
+            .map(|entry| {
+                let stream = entry.fields.iter().find(|(k, _)| k == STREAM_FIELD).unwrap().1.clone();
+                (stream, entry.realtime_timestamp.unwrap_or_default(), entry.message.text().unwrap().into_owned())
+            })
+            .collect()
+    }
+
+    fn expected(list: &[(&str, &str, &str)]) -> Vec<(String, String, String)> {
+        list.iter().map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string())).collect()
+    }
+
+    #[test]
+    fn cri_chunks_join_until_the_final_one() {
+        let mut reader = ContainerReader::new(ContainerFormat::Cri, ContainerInfo::default());
+        let entries = push_all(&mut reader, &[
+            "2026-10-16T10:00:00.000001Z stdout P first ",
+            "2026-10-16T10:00:00.000002Z stdout P second ",
+            "2026-10-16T10:00:00.000003Z stdout F:x third",
+            "2026-10-16T10:00:01Z stdout F",
+        ]);
+        // The joined entry keeps the first chunk's time; an empty F line is an empty message
+        assert_eq!(entries, expected(&[
+            ("stdout", "1792144800000001", "first second third"),
+            ("stdout", "1792144801000000", ""),
+        ]));
+    }
+
+    #[test]
+    fn cri_streams_join_separately() {
+        let mut reader = ContainerReader::new(ContainerFormat::Cri, ContainerInfo::default());
+        let entries = push_all(&mut reader, &[
+            "2026-10-16T10:00:00Z stdout P out-1 ",
+            "2026-10-16T10:00:01Z stderr P err-1 ",
+            "2026-10-16T10:00:02Z stdout F out-2",
+            "2026-10-16T10:00:03Z stderr F err-2",
+        ]);
+        assert_eq!(entries, expected(&[
+            ("stdout", "1792144800000000", "out-1 out-2"),
+            ("stderr", "1792144801000000", "err-1 err-2"),
+        ]));
+    }
+
+    #[test]
+    fn docker_lines_without_a_newline_are_partial() {
+        let mut reader = ContainerReader::new(ContainerFormat::Docker, ContainerInfo::default());
+        let entries = push_all(&mut reader, &[
+            r#"{"log":"a long ","stream":"stdout","time":"2026-10-16T10:00:00Z"}"#,
+            r#"{"log":"oops\r\n","stream":"stderr","time":"2026-10-16T10:00:01Z"}"#,
+            r#"{"log":"line ","stream":"stdout","time":"2026-10-16T10:00:02Z"}"#,
+            r#"{"log":"ends here\n","stream":"stdout","time":"2026-10-16T10:00:03Z"}"#,
+        ]);
+        assert_eq!(entries, expected(&[
+            ("stderr", "1792144801000000", "oops"),
+            ("stdout", "1792144800000000", "a long line ends here"),
+        ]));
+    }
+
+    #[test]
+    fn stderr_is_warn_and_names_come_from_the_path() {
+        let id = "0123456789abcdef".repeat(4);
+        let path = format!("/var/log/containers/web-5d8f_shop_nginx-{}.log", id);
+        let mut reader = ContainerReader::new(ContainerFormat::Cri, ContainerInfo::from_path(Path::new(&path)));
+        let Pushed::Entry(entry) = reader.push("2026-10-16T10:00:00Z stderr F boom") else {
+            panic!("expected an entry");
+        };
+        assert_eq!(entry.priority, Some(STDERR_PRIORITY.to_string()));
+        assert_eq!(entry.syslog_identifier.as_deref(), Some("nginx"));
+        let field = |key: &str| entry.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(field("K8S_NAMESPACE"), Some("shop"));
+        assert_eq!(field("K8S_POD"), Some("web-5d8f"));
+        assert_eq!(field("CONTAINER_ID"), Some("0123456789ab"));
+
+        assert!(matches!(reader.push("Oct 16 10:00:00 host app: not a container line"), Pushed::Unparsed));
+        assert_eq!(ContainerFormat::detect(r#"{"log":"x\n","stream":"stdout","time":"2026-10-16T10:00:00Z"}"#),
+                   Some(ContainerFormat::Docker));
+    }
+}
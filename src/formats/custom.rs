@@ -0,0 +1,243 @@
+//! User-defined line formats from `~/.config/jlog/formats.json`, for logs jlog has no
+//! built-in parser for. Each format is a regex with named groups: `timestamp`, `priority`,
+//! `service` and `message` fill the entry's columns, every other named group becomes a field.
+//!
+//! ```json
+//! [{
+//!     "name": "myapp",
+//!     "regex": "^(?P<timestamp>\\S+ \\S+) \\[(?P<priority>\\w)\\] (?P<service>\\w+): (?P<message>.*)$",
+//!     "timestamp_format": "%Y-%m-%d %H:%M:%S%.f",
+//!     "priorities": { "E": 3, "W": 4, "I": 6, "D": 7 }
+//! }]
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::analyzer::logfmt::level_priority;
+use crate::journalctl::{JournalEntry, JournalValue, SyslogClock, parse_decimal_micros};
+
+/// Group names that map to entry columns rather than fields.
+const COLUMN_GROUPS: [&str; 4] = ["timestamp", "priority", "service", "message"];
+
+/// One entry of formats.json, as written by the user.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FormatDef {
+    pub name: String,
+    pub regex: String,
+    /// chrono format for the `timestamp` group (e.g. "%d/%b/%Y:%H:%M:%S %z"). Empty tries
+    /// RFC 3339, epoch seconds, "YYYY-MM-DD HH:MM:SS" and BSD syslog stamps.
+    #[serde(default)]
+    pub timestamp_format: String,
+    /// Value of the `priority` group → syslog severity (0-7). Values not listed fall back to
+    /// level names ("warn", "error", ...) and numbers 0-7.
+    #[serde(default)]
+    pub priorities: BTreeMap<String, u8>,
+}
+
+/// A compiled user format.
+#[derive(Debug)]
+pub struct CustomFormat {
+    pub name: String,
+    regex: Regex,
+    timestamp_format: Option<String>,
+    /// `priorities` with lowercased keys
+    priorities: HashMap<String, u8>,
+    field_groups: Vec<String>,
+}
+
+impl CustomFormat {
+    pub fn compile(def: &FormatDef) -> Result<Self, String> {
+        let regex = Regex::new(&def.regex).map_err(|e| format!("format \"{}\": {}", def.name, e))?;
+        if regex.capture_names().flatten().all(|name| name != "message") {
+            return Err(format!("format \"{}\" has no (?P<message>...) group", def.name));
+        }
+        if let Some((value, priority)) = def.priorities.iter().find(|(_, p)| **p > 7) {
+            return Err(format!("format \"{}\": priority {} for \"{}\" is not 0-7", def.name, priority, value));
+        }
+        let field_groups = regex.capture_names()
+            .flatten()
+            .filter(|name| !COLUMN_GROUPS.contains(name))
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            name: def.name.clone(),
+            regex,
+            timestamp_format: Some(def.timestamp_format.trim().to_string()).filter(|f| !f.is_empty()),
+            priorities: def.priorities.iter().map(|(k, v)| (k.to_lowercase(), *v)).collect(),
+            field_groups,
+        })
+    }
+
+    /// Parse `line`, or None if the regex doesn't match.
+    pub fn parse(&self, line: &str, clock: &SyslogClock) -> Option<JournalEntry> {
+        let caps = self.regex.captures(line)?;
+        let group = |name: &str| caps.name(name).map(|m| m.as_str()).filter(|s| !s.is_empty());
+        let message = group("message").unwrap_or_default().to_string();
+
+        let realtime_us = group("timestamp").and_then(|stamp| match self.timestamp_format {
+            Some(ref format) => clock.resolve_format(stamp, format),
+            None => guess_timestamp(stamp, clock),
+        });
+
+        let reported = group("priority").and_then(|value| {
+            self.priorities.get(&value.to_lowercase()).copied().or_else(|| level_priority(value))
+        });
+
+        let fields = self.field_groups.iter()
+            .filter_map(|name| group(name).map(|value| (name.clone(), value.to_string())))
+            .collect();
+
+        Some(JournalEntry {
+            realtime_timestamp: realtime_us.map(|t| t.to_string()),
+            priority: reported.map(|p| p.to_string()),
+            facility: None,
+            syslog_identifier: Some(group("service").unwrap_or(&self.name).to_string()),
+            systemd_unit: None,
+            message: JournalValue::Text(message),
+            fields,
+            priority_inferred: reported.is_none(),
+            extra: BTreeMap::new(),
+        })
+    }
+}
+
+/// Timestamp formats tried when a format doesn't name one.
+fn guess_timestamp(stamp: &str, clock: &SyslogClock) -> Option<i64> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(stamp) {
+        return Some(dt.timestamp_micros());
+    }
+    if stamp.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return parse_decimal_micros(stamp);
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%d/%b/%Y:%H:%M:%S %z", "%b %d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| clock.resolve_format(stamp, format))
+}
+
+pub fn formats_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_default();
+    PathBuf::from(home).join(".config").join("jlog").join("formats.json")
+}
+
+/// Read and compile formats.json. A missing file means no user formats.
+pub fn load_formats() -> Result<Vec<CustomFormat>, String> {
+    let path = formats_path();
+    let data = match std::fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let defs: Vec<FormatDef> = serde_json::from_str(&data)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    defs.iter().map(CustomFormat::compile).collect()
+}
+
+/// Names of the formats in formats.json, for the format pickers (empty if it can't be read).
+pub fn format_names() -> Vec<String> {
+    std::fs::read_to_string(formats_path())
+        .ok()
+        .and_then(|data| serde_json::from_str::<Vec<FormatDef>>(&data).ok())
+        .map(|defs| defs.into_iter().map(|def| def.name).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2023-10-11 16:00:00 UTC
+    const TIME_US: i64 = 1_697_040_000_000_000;
+
+    fn format(regex: &str, timestamp_format: &str, priorities: &[(&str, u8)]) -> CustomFormat {
+        CustomFormat::compile(&FormatDef {
+            name: "myapp".to_string(),
+            regex: regex.to_string(),
+            timestamp_format: timestamp_format.to_string(),
+            priorities: priorities.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        })
+        .unwrap()
+    }
+
+    fn clock(year: &str, tz: &str) -> SyslogClock {
+        SyslogClock::from_settings(year, tz, "").unwrap()
+    }
+
+    #[test]
+    fn parse_fills_columns_and_fields() {
+        let format = format(
+            r"^(?P<timestamp>\S+ \S+) \[(?P<priority>\w+)\] (?P<service>\w+) req=(?P<req_id>\S+) (?P<message>.*)$",
+            "%Y-%m-%d %H:%M:%S%.f",
+            &[("E", 3)],
+        );
+        let entry = format.parse("2023-10-11 16:00:00.5 [e] api req=abc12 upstream timed out", &clock("", "")).unwrap();
+        assert_eq!(entry.realtime_timestamp.as_deref(), Some("1697040000500000"));
+        assert_eq!(entry.priority.as_deref(), Some("3"));
+        assert!(!entry.priority_inferred);
+        assert_eq!(entry.syslog_identifier.as_deref(), Some("api"));
+        assert_eq!(entry.fields, [("req_id".to_string(), "abc12".to_string())]);
+        assert!(matches!(entry.message, JournalValue::Text(ref m) if m == "upstream timed out"));
+
+        // Level names work without a mapping; unknown values leave the priority to the rules
+        let entry = format.parse("2023-10-11 16:00:00 [warning] api req=x slow", &clock("", "")).unwrap();
+        assert_eq!(entry.priority.as_deref(), Some("4"));
+        let entry = format.parse("2023-10-11 16:00:00 [chatty] api req=x hello", &clock("", "")).unwrap();
+        assert_eq!(entry.priority, None);
+        assert!(entry.priority_inferred);
+
+        assert!(format.parse("not in this format", &clock("", "")).is_none());
+    }
+
+    #[test]
+    fn service_defaults_to_format_name() {
+        let format = format(r"^(?P<message>.*)$", "", &[]);
+        let entry = format.parse("hello", &clock("", "")).unwrap();
+        assert_eq!(entry.syslog_identifier.as_deref(), Some("myapp"));
+        assert_eq!(entry.realtime_timestamp, None);
+    }
+
+    #[test]
+    fn compile_errors() {
+        let def = |regex: &str, priorities: &[(&str, u8)]| FormatDef {
+            name: "bad".to_string(),
+            regex: regex.to_string(),
+            timestamp_format: String::new(),
+            priorities: priorities.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        };
+        assert!(CustomFormat::compile(&def("(?P<message>.*", &[])).is_err());
+        assert!(CustomFormat::compile(&def("(?P<msg>.*)", &[])).unwrap_err().contains("message"));
+        assert!(CustomFormat::compile(&def("(?P<message>.*)", &[("X", 8)])).unwrap_err().contains("0-7"));
+    }
+
+    #[test]
+    fn resolve_format() {
+        let berlin = clock("", "Europe/Berlin");
+        assert_eq!(berlin.resolve_format("2023-10-11 18:00:00", "%Y-%m-%d %H:%M:%S"), Some(TIME_US));
+        // A zone in the stamp beats the source's zone
+        assert_eq!(berlin.resolve_format("11/Oct/2023:12:00:00 -0400", "%d/%b/%Y:%H:%M:%S %z"), Some(TIME_US));
+        // No year: the one set, else inferred
+        assert_eq!(clock("2023", "").resolve_format("10-11 16:00:00", "%m-%d %H:%M:%S"), Some(TIME_US));
+        assert!(clock("", "").resolve_format("10-11 16:00:00", "%m-%d %H:%M:%S").is_some());
+        assert_eq!(berlin.resolve_format("garbage", "%Y-%m-%d %H:%M:%S"), None);
+    }
+
+    #[test]
+    fn guessed_timestamps() {
+        let utc = clock("2023", "");
+        for stamp in [
+            "2023-10-11T18:00:00+02:00",
+            "1697040000",
+            "2023-10-11 16:00:00",
+            "2023-10-11T16:00:00.000",
+            "11/Oct/2023:16:00:00 +0000",
+            "Oct 11 16:00:00",
+        ] {
+            assert_eq!(guess_timestamp(stamp, &utc), Some(TIME_US), "{}", stamp);
+        }
+        assert_eq!(guess_timestamp("1697040000.25", &utc), Some(TIME_US + 250_000));
+        assert_eq!(guess_timestamp("yesterday", &utc), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Kernel ring buffer dumps from `dmesg` (`[  123.456789] usb 1-1: new device`), with the
+//! raw `<N>` priority prefix `dmesg -r` adds and the `[T123]` caller id of kernels built with
+//! CONFIG_PRINTK_CALLER.
+//!
+//! The bracketed value is seconds since boot. It becomes a wall-clock time once the boot
+//! time is known, from the settings or from the kernel's "setting system clock" message;
+//! like `dmesg -T`, that is off by however long the machine was suspended.
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use crate::journalctl::{JournalEntry, JournalValue, parse_decimal_micros};
+
+/// "[<PRI>][  123.456789] [[T123] ]message"
+static DMESG_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:<(\d{1,3})>)?\[\s*(\d+\.\d+)\] (?:\[\s*([TC]\d+)\] )?(.*)$").unwrap()
+});
+
+/// Lines searched for the system clock message. It is logged early in boot, and a file
+/// that doesn't start with the boot has none.
+const BOOT_TIME_SCAN_LINES: usize = 5000;
+
+/// "rtc_cmos 00:00: setting system clock to 2026-10-16T22:54:47 UTC (1792191287)"
+static SYSTEM_CLOCK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"setting system clock to .*\((\d+)\)\s*$").unwrap()
+});
+
+/// Parse one dmesg line. `boot_us` is the wall-clock time of boot, if known.
+pub fn parse_dmesg_line(line: &str, boot_us: Option<i64>) -> Option<JournalEntry> {
+    let caps = DMESG_LINE_REGEX.captures(line)?;
+    let pri: Option<u8> = caps.get(1).and_then(|m| m.as_str().parse().ok()).filter(|&p| p <= 191);
+    let monotonic_us = parse_decimal_micros(&caps[2])?;
+    let message = caps[4].to_string();
+
+    let mut fields = vec![("__MONOTONIC_TIMESTAMP".to_string(), monotonic_us.to_string())];
+    if let Some(caller) = caps.get(3) {
+        fields.push(("PRINTK_CALLER".to_string(), caller.as_str().to_string()));
+    }
+
+    Some(JournalEntry {
+        realtime_timestamp: boot_us.and_then(|boot| boot.checked_add(monotonic_us)).map(|t| t.to_string()),
+        priority: pri.map(|pri| (pri & 7).to_string()),
+        facility: pri.map(|pri| (pri >> 3).to_string()),
+        syslog_identifier: Some("kernel".to_string()),
+        systemd_unit: None,
+        message: JournalValue::Text(message),
+        fields,
+        priority_inferred: pri.is_none(),
+        extra: BTreeMap::new(),
+    })
+}
+
+/// Find the boot time in a dmesg dump from the kernel setting the system clock from the
+/// RTC early in boot: its wall-clock time minus its time since boot. Only the head of the
+/// file is searched.
+pub fn find_boot_time(reader: impl BufRead) -> Option<i64> {
+    reader.lines()
+        .take(BOOT_TIME_SCAN_LINES)
+        .map_while(Result::ok)
+        .find_map(|line| {
+            let caps = DMESG_LINE_REGEX.captures(line.trim())?;
+            let epoch: i64 = SYSTEM_CLOCK_REGEX.captures(&caps[4])?[1].parse().ok()?;
+            epoch.checked_mul(1_000_000)?.checked_sub(parse_decimal_micros(&caps[2])?)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-10-16 22:54:47 UTC, in microseconds
+    const CLOCK_US: i64 = 1_792_191_287_000_000;
+
+    fn field<'a>(entry: &'a JournalEntry, key: &str) -> Option<&'a str> {
+        entry.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn plain_line_is_relative_until_boot_is_known() {
+        let line = "[    1.234567] usb 1-1: new high-speed USB device number 2";
+        let entry = parse_dmesg_line(line, None).unwrap();
+        assert_eq!(entry.realtime_timestamp, None);
+        assert_eq!(field(&entry, "__MONOTONIC_TIMESTAMP"), Some("1234567"));
+        assert_eq!(entry.message.text().as_deref(), Some("usb 1-1: new high-speed USB device number 2"));
+        assert_eq!(entry.syslog_identifier.as_deref(), Some("kernel"));
+        assert!(entry.priority_inferred);
+
+        let entry = parse_dmesg_line(line, Some(CLOCK_US)).unwrap();
+        assert_eq!(entry.realtime_timestamp, Some((CLOCK_US + 1_234_567).to_string()));
+    }
+
+    #[test]
+    fn raw_priority_and_caller_id() {
+        let entry = parse_dmesg_line("<3>[   12.500000] [  T412] ata1: COMRESET failed", None).unwrap();
+        assert_eq!((entry.priority.as_deref(), entry.facility.as_deref()), (Some("3"), Some("0")));
+        assert!(!entry.priority_inferred);
+        assert_eq!(field(&entry, "PRINTK_CALLER"), Some("T412"));
+        assert_eq!(entry.message.text().as_deref(), Some("ata1: COMRESET failed"));
+
+        // A facility other than kern, as `dmesg -r` shows for userspace writes to /dev/kmsg
+        let entry = parse_dmesg_line("<14>[    5.000000] [    C0] hello", None).unwrap();
+        assert_eq!((entry.priority.as_deref(), entry.facility.as_deref()), (Some("6"), Some("1")));
+        assert_eq!(field(&entry, "PRINTK_CALLER"), Some("C0"));
+
+        // Out of range PRI is ignored, not the line
+        let entry = parse_dmesg_line("<999>[    5.000000] hello", None).unwrap();
+        assert_eq!(entry.priority, None);
+        assert!(parse_dmesg_line("Oct 11 22:14:15 host kernel: hello", None).is_none());
+    }
+
+    #[test]
+    fn boot_time_from_the_system_clock_message() {
+        let dump = "[    0.000000] Linux version 6.1.0\n\
+                    [    0.812345] rtc_cmos 00:00: setting system clock to 2026-10-16T22:54:47 UTC (1792191287)\n\
+                    [    3.000000] systemd[1]: started\n";
+        assert_eq!(find_boot_time(dump.as_bytes()), Some(CLOCK_US - 812_345));
+        assert_eq!(find_boot_time("[    0.000000] Linux version 6.1.0\n".as_bytes()), None);
+        // An epoch too large for microseconds is no boot time
+        let huge = "[    0.500000] rtc_cmos 00:00: setting system clock to ? (99999999999999999)\n";
+        assert_eq!(find_boot_time(huge.as_bytes()), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Reader for the journal export format (`journalctl -o export`, as consumed by
+//! systemd-journal-remote).
+//!
+//! Each record is a run of fields terminated by an empty line. A field is either
+//! `NAME=value\n`, or, for values that contain newlines or binary data,
+//! `NAME\n` followed by the value length as le64, the raw bytes and a `\n`.
+
+use std::io::{self, BufRead, Read};
+use crate::formats::journal_file::RawEntry;
+
+/// True if a stream starting with `head` is in export format. journalctl always writes the
+/// cursor and timestamps first, which no text log format starts with.
+pub fn is_export_stream(head: &[u8]) -> bool {
+    head.starts_with(b"__CURSOR=") || head.starts_with(b"__REALTIME_TIMESTAMP=")
+}
+
+pub struct ExportReader<R> {
+    reader: R,
+}
+
+impl<R: BufRead> ExportReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// Read the next record, or None at end of stream. A final record without the
+    /// terminating empty line (e.g. a truncated capture) is still returned.
+    pub fn next_entry(&mut self) -> io::Result<Option<RawEntry>> {
+        let mut fields = Vec::new();
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok((!fields.is_empty()).then_some(fields));
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+
+            if line.is_empty() {
+                if fields.is_empty() {
+                    continue; // tolerate extra blank lines between records
+                }
+                return Ok(Some(fields));
+            }
+
+            if let Some(eq) = line.iter().position(|&b| b == b'=') {
+                let name = String::from_utf8_lossy(&line[..eq]).into_owned();
+                fields.push((name, line[eq + 1..].to_vec()));
+            } else {
+                let name = String::from_utf8_lossy(&line).into_owned();
+                let value = self.read_binary_value(&name)?;
+                fields.push((name, value));
+            }
+        }
+    }
+
+    /// Read the le64 length, value bytes and trailing newline of a binary-framed field.
+    fn read_binary_value(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        let mut len = [0u8; 8];
+        self.reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+
+        let mut value = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut value)?;
+        if (value.len() as u64) < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("export field {} is truncated", name),
+            ));
+        }
+
+        let mut newline = [0u8; 1];
+        self.reader.read_exact(&mut newline)?;
+        if newline[0] != b'\n' {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("export field {} is not followed by a newline", name),
+            ));
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A field in the binary framing: name, le64 length, bytes, newline.
+    fn binary_field(name: &str, value: &[u8]) -> Vec<u8> {
+        let mut out = format!("{}\n", name).into_bytes();
+        out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        out.extend_from_slice(value);
+        out.push(b'\n');
+        out
+    }
+
+    fn read_all(data: &[u8]) -> io::Result<Vec<RawEntry>> {
+        let mut reader = ExportReader::new(data);
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    fn field(name: &str, value: &[u8]) -> (String, Vec<u8>) {
+        (name.to_string(), value.to_vec())
+    }
+
+    #[test]
+    fn text_and_binary_fields_mix() {
+        let mut data = b"__CURSOR=s=1\n__REALTIME_TIMESTAMP=1792144800000000\n".to_vec();
+        // The value holds a newline and an `=`, which only the length framing allows
+        data.extend(binary_field("MESSAGE", b"line one\nkey=value\xff"));
+        data.extend_from_slice(b"PRIORITY=3\n\n\n__CURSOR=s=2\n");
+        data.extend(binary_field("MESSAGE", b""));
+        data.extend_from_slice(b"\n");
+
+        assert_eq!(read_all(&data).unwrap(), [
+            vec![
+                field("__CURSOR", b"s=1"),
+                field("__REALTIME_TIMESTAMP", b"1792144800000000"),
+                field("MESSAGE", b"line one\nkey=value\xff"),
+                field("PRIORITY", b"3"),
+            ],
+            vec![field("__CURSOR", b"s=2"), field("MESSAGE", b"")],
+        ]);
+    }
+
+    #[test]
+    fn last_record_may_lack_its_blank_line() {
+        assert_eq!(read_all(b"__CURSOR=s=1\n\n__CURSOR=s=2\nMESSAGE=hi").unwrap(), [
+            vec![field("__CURSOR", b"s=1")],
+            vec![field("__CURSOR", b"s=2"), field("MESSAGE", b"hi")],
+        ]);
+    }
+
+    #[test]
+    fn broken_binary_framing_is_an_error() {
+        let mut short = b"MESSAGE\n".to_vec();
+        short.extend_from_slice(&10u64.to_le_bytes());
+        short.extend_from_slice(b"abc");
+        assert_eq!(read_all(&short).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut no_newline = b"MESSAGE\n".to_vec();
+        no_newline.extend_from_slice(&3u64.to_le_bytes());
+        no_newline.extend_from_slice(b"abcX\n");
+        assert_eq!(read_all(&no_newline).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // The length itself cut short
+        assert_eq!(read_all(b"MESSAGE\n\x03\x00").unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn export_streams_are_recognised() {
+        assert!(is_export_stream(b"__CURSOR=s=abc;i=1\n"));
+        assert!(is_export_stream(b"__REALTIME_TIMESTAMP=1\n"));
+        assert!(!is_export_stream(b"MESSAGE=hi\n"));
+    }
+}
@@ -0,0 +1,383 @@
+//! Reader for systemd's binary `.journal` files, so archived journals copied off a machine
+//! (e.g. from `/var/log/journal/<machine-id>/`) can be opened without journalctl.
+//!
+//! Only the parts needed to walk entries in order are decoded: the header, the global
+//! entry-array chain, ENTRY objects and the DATA objects they reference. Hash tables and
+//! FSS tags are ignored.
+
+use std::io::Read;
+use anyhow::{Context, bail};
+
+pub const SIGNATURE: &[u8; 8] = b"LPKSHHRH";
+
+const HEADER_INCOMPATIBLE_COMPRESSED_XZ: u32 = 1 << 0;
+const HEADER_INCOMPATIBLE_COMPRESSED_LZ4: u32 = 1 << 1;
+const HEADER_INCOMPATIBLE_KEYED_HASH: u32 = 1 << 2;
+const HEADER_INCOMPATIBLE_COMPRESSED_ZSTD: u32 = 1 << 3;
+const HEADER_INCOMPATIBLE_COMPACT: u32 = 1 << 4;
+const HEADER_INCOMPATIBLE_SUPPORTED: u32 = HEADER_INCOMPATIBLE_COMPRESSED_XZ
+    | HEADER_INCOMPATIBLE_COMPRESSED_LZ4
+    | HEADER_INCOMPATIBLE_KEYED_HASH
+    | HEADER_INCOMPATIBLE_COMPRESSED_ZSTD
+    | HEADER_INCOMPATIBLE_COMPACT;
+
+const OBJECT_DATA: u8 = 1;
+const OBJECT_ENTRY: u8 = 3;
+const OBJECT_ENTRY_ARRAY: u8 = 6;
+
+const OBJECT_COMPRESSED_XZ: u8 = 1 << 0;
+const OBJECT_COMPRESSED_LZ4: u8 = 1 << 1;
+const OBJECT_COMPRESSED_ZSTD: u8 = 1 << 2;
+
+const OBJECT_HEADER_SIZE: u64 = 16;
+const ENTRY_ITEMS_OFFSET: u64 = 64;
+const ENTRY_ARRAY_ITEMS_OFFSET: u64 = 24;
+
+/// True if `bytes` starts with the journal file signature.
+pub fn is_journal_file(bytes: &[u8]) -> bool {
+    bytes.starts_with(SIGNATURE)
+}
+
+/// One decoded journal entry: its `NAME=value` fields in file order, plus the
+/// `__CURSOR`, `__REALTIME_TIMESTAMP` and `__MONOTONIC_TIMESTAMP` fields journalctl adds
+/// on export.
+pub type RawEntry = Vec<(String, Vec<u8>)>;
+
+pub struct JournalFile {
+    data: Vec<u8>,
+    compact: bool,
+    seqnum_id: [u8; 16],
+    n_entries: u64,
+    entry_array_offset: u64,
+}
+
+impl JournalFile {
+    pub fn parse(data: Vec<u8>) -> anyhow::Result<Self> {
+        if !is_journal_file(&data) {
+            bail!("not a systemd journal file");
+        }
+        let mut file = JournalFile {
+            data,
+            compact: false,
+            seqnum_id: [0; 16],
+            n_entries: 0,
+            entry_array_offset: 0,
+        };
+
+        let incompatible = file.u32_at(12).context("truncated journal header")?;
+        if incompatible & !HEADER_INCOMPATIBLE_SUPPORTED != 0 {
+            bail!("unsupported journal file features (flags {:#x})", incompatible);
+        }
+        file.compact = incompatible & HEADER_INCOMPATIBLE_COMPACT != 0;
+        file.seqnum_id = file.bytes_at(72, 16)
+            .context("truncated journal header")?
+            .try_into()
+            .unwrap();
+
+        let header_size = file.u64_at(88).context("truncated journal header")?;
+        file.n_entries = if header_size > 152 { file.u64_at(152).unwrap_or(0) } else { 0 };
+        file.entry_array_offset = if header_size > 176 { file.u64_at(176).unwrap_or(0) } else { 0 };
+        Ok(file)
+    }
+
+    /// Offsets of all ENTRY objects in the order they were written.
+    pub fn entry_offsets(&self) -> anyhow::Result<Vec<u64>> {
+        let item_size = if self.compact { 4 } else { 8 };
+        let mut offsets = Vec::with_capacity(self.n_entries.min(1 << 24) as usize);
+        let mut array = self.entry_array_offset;
+        let mut visited = std::collections::HashSet::new();
+
+        while array != 0 {
+            if !visited.insert(array) {
+                bail!("entry array chain loops at offset {}", array);
+            }
+            let size = self.object_header(array, OBJECT_ENTRY_ARRAY)?;
+            let next = self.u64_at(array + OBJECT_HEADER_SIZE).context("truncated entry array")?;
+
+            let mut pos = array + ENTRY_ARRAY_ITEMS_OFFSET;
+            while pos + item_size <= array + size {
+                let offset = if self.compact {
+                    self.u32_at(pos).map(u64::from)
+                } else {
+                    self.u64_at(pos)
+                }
+                .context("truncated entry array")?;
+                // Arrays are preallocated; unused trailing slots are zero
+                if offset == 0 {
+                    break;
+                }
+                offsets.push(offset);
+                pos += item_size;
+            }
+            array = next;
+        }
+        Ok(offsets)
+    }
+
+    /// Decode the ENTRY object at `offset` and every DATA object it references.
+    pub fn read_entry(&self, offset: u64) -> anyhow::Result<RawEntry> {
+        let size = self.object_header(offset, OBJECT_ENTRY)?;
+        let truncated = || anyhow::anyhow!("truncated entry at offset {}", offset);
+        let seqnum = self.u64_at(offset + 16).ok_or_else(truncated)?;
+        let realtime = self.u64_at(offset + 24).ok_or_else(truncated)?;
+        let monotonic = self.u64_at(offset + 32).ok_or_else(truncated)?;
+        let boot_id = self.bytes_at(offset + 40, 16).ok_or_else(truncated)?;
+        let xor_hash = self.u64_at(offset + 56).ok_or_else(truncated)?;
+
+        let item_size = if self.compact { 4 } else { 16 };
+        let mut fields = Vec::new();
+        let mut pos = offset + ENTRY_ITEMS_OFFSET;
+        while pos + item_size <= offset + size {
+            let data_offset = if self.compact {
+                self.u32_at(pos).map(u64::from)
+            } else {
+                self.u64_at(pos)
+            }
+            .ok_or_else(truncated)?;
+            pos += item_size;
+
+            let payload = self.read_data(data_offset)?;
+            let Some(eq) = payload.iter().position(|&b| b == b'=') else {
+                continue;
+            };
+            let name = String::from_utf8_lossy(&payload[..eq]).into_owned();
+            fields.push((name, payload[eq + 1..].to_vec()));
+        }
+
+        let boot_id = hex(boot_id);
+        let cursor = format!(
+            "s={};i={:x};b={};m={:x};t={:x};x={:x}",
+            hex(&self.seqnum_id), seqnum, boot_id, monotonic, realtime, xor_hash
+        );
+        fields.push(("__CURSOR".to_string(), cursor.into_bytes()));
+        fields.push(("__REALTIME_TIMESTAMP".to_string(), realtime.to_string().into_bytes()));
+        fields.push(("__MONOTONIC_TIMESTAMP".to_string(), monotonic.to_string().into_bytes()));
+        // journald also stores _BOOT_ID as a regular field; only older writers omit it
+        if !fields.iter().any(|(name, _)| name == "_BOOT_ID") {
+            fields.push(("_BOOT_ID".to_string(), boot_id.into_bytes()));
+        }
+        Ok(fields)
+    }
+
+    /// Payload of a DATA object (`NAME=value`), decompressed if needed.
+    fn read_data(&self, offset: u64) -> anyhow::Result<Vec<u8>> {
+        let size = self.object_header(offset, OBJECT_DATA)?;
+        let flags = self.data[offset as usize + 1];
+        let payload_offset = if self.compact { 72 } else { 64 };
+        if size < payload_offset {
+            bail!("data object at offset {} is too small", offset);
+        }
+        let payload = self.bytes_at(offset + payload_offset, size - payload_offset)
+            .with_context(|| format!("truncated data object at offset {}", offset))?;
+
+        if flags & OBJECT_COMPRESSED_XZ != 0 {
+            let mut out = Vec::new();
+            xz2::read::XzDecoder::new(payload).read_to_end(&mut out)
+                .context("corrupt XZ data object")?;
+            Ok(out)
+        } else if flags & OBJECT_COMPRESSED_LZ4 != 0 {
+            // systemd prefixes the LZ4 block with the uncompressed size as le64
+            if payload.len() < 8 {
+                bail!("corrupt LZ4 data object at offset {}", offset);
+            }
+            let size = u64::from_le_bytes(payload[..8].try_into().unwrap()) as usize;
+            lz4_flex::block::decompress(&payload[8..], size).context("corrupt LZ4 data object")
+        } else if flags & OBJECT_COMPRESSED_ZSTD != 0 {
+            let mut out = Vec::new();
+            ruzstd::decoding::StreamingDecoder::new(payload)
+                .map_err(|e| anyhow::anyhow!("corrupt ZSTD data object: {}", e))?
+                .read_to_end(&mut out)
+                .context("corrupt ZSTD data object")?;
+            Ok(out)
+        } else {
+            Ok(payload.to_vec())
+        }
+    }
+
+    /// Validate the object header at `offset` and return the object's size.
+    fn object_header(&self, offset: u64, expected_type: u8) -> anyhow::Result<u64> {
+        let object_type = self.bytes_at(offset, 1)
+            .with_context(|| format!("object offset {} is past the end of the file", offset))?[0];
+        if object_type != expected_type {
+            bail!("expected object type {} at offset {}, found {}", expected_type, offset, object_type);
+        }
+        let size = self.u64_at(offset + 8).context("truncated object header")?;
+        if size < OBJECT_HEADER_SIZE || self.bytes_at(offset, size).is_none() {
+            bail!("object at offset {} has invalid size {}", offset, size);
+        }
+        Ok(size)
+    }
+
+    fn bytes_at(&self, offset: u64, len: u64) -> Option<&[u8]> {
+        let start = usize::try_from(offset).ok()?;
+        let end = start.checked_add(usize::try_from(len).ok()?)?;
+        self.data.get(start..end)
+    }
+
+    fn u32_at(&self, offset: u64) -> Option<u32> {
+        self.bytes_at(offset, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    fn u64_at(&self, offset: u64) -> Option<u64> {
+        self.bytes_at(offset, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER_SIZE: u64 = 256;
+
+    /// Builds a journal file: a header, an entry array sized for `n_entries` right after it
+    /// (so cutting the file short keeps it), then whatever DATA and ENTRY objects are added.
+    struct Builder {
+        data: Vec<u8>,
+        compact: bool,
+        array: u64,
+        entries: usize,
+    }
+
+    impl Builder {
+        fn new(compact: bool, n_entries: usize) -> Self {
+            let mut data = vec![0; HEADER_SIZE as usize];
+            data[..8].copy_from_slice(SIGNATURE);
+            let incompatible = if compact { HEADER_INCOMPATIBLE_COMPACT } else { 0 };
+            data[12..16].copy_from_slice(&incompatible.to_le_bytes());
+            data[72..88].copy_from_slice(&[0xab; 16]);
+            data[88..96].copy_from_slice(&HEADER_SIZE.to_le_bytes());
+            data[152..160].copy_from_slice(&(n_entries as u64).to_le_bytes());
+            let mut builder = Builder { data, compact, array: 0, entries: 0 };
+            let item_size = if compact { 4 } else { 8 };
+            // next_entry_array_offset, then the items
+            let array = vec![0; 8 + n_entries * item_size];
+            builder.array = builder.object(OBJECT_ENTRY_ARRAY, 0, &array);
+            let array = builder.array;
+            builder.data[176..184].copy_from_slice(&array.to_le_bytes());
+            builder
+        }
+
+        /// Append an object with `body` after its 16-byte header; returns its offset.
+        fn object(&mut self, object_type: u8, flags: u8, body: &[u8]) -> u64 {
+            let offset = self.data.len() as u64;
+            let size = OBJECT_HEADER_SIZE + body.len() as u64;
+            self.data.extend_from_slice(&[object_type, flags, 0, 0, 0, 0, 0, 0]);
+            self.data.extend_from_slice(&size.to_le_bytes());
+            self.data.extend_from_slice(body);
+            while !self.data.len().is_multiple_of(8) {
+                self.data.push(0);
+            }
+            offset
+        }
+
+        fn data_object(&mut self, flags: u8, payload: &[u8]) -> u64 {
+            // hash, next_hash_offset, next_field_offset, entry_offset, entry_array_offset,
+            // n_entries, and in compact files tail_entry_array_offset/_n_entries
+            let mut body = vec![0; if self.compact { 56 } else { 48 }];
+            body.extend_from_slice(payload);
+            self.object(OBJECT_DATA, flags, &body)
+        }
+
+        fn entry(&mut self, seqnum: u64, realtime: u64, payloads: &[(u8, &[u8])]) -> u64 {
+            let data: Vec<u64> = payloads.iter().map(|(flags, payload)| self.data_object(*flags, payload)).collect();
+            let mut body = Vec::new();
+            body.extend_from_slice(&seqnum.to_le_bytes());
+            body.extend_from_slice(&realtime.to_le_bytes());
+            body.extend_from_slice(&(realtime - 1_000_000).to_le_bytes());
+            body.extend_from_slice(&[0x11; 16]);
+            body.extend_from_slice(&0x1234u64.to_le_bytes());
+            for offset in data {
+                if self.compact {
+                    body.extend_from_slice(&(offset as u32).to_le_bytes());
+                } else {
+                    body.extend_from_slice(&offset.to_le_bytes());
+                    body.extend_from_slice(&0u64.to_le_bytes());
+                }
+            }
+            let offset = self.object(OBJECT_ENTRY, 0, &body);
+
+            let item = (self.array + ENTRY_ARRAY_ITEMS_OFFSET) as usize;
+            if self.compact {
+                let item = item + self.entries * 4;
+                self.data[item..item + 4].copy_from_slice(&(offset as u32).to_le_bytes());
+            } else {
+                let item = item + self.entries * 8;
+                self.data[item..item + 8].copy_from_slice(&offset.to_le_bytes());
+            }
+            self.entries += 1;
+            offset
+        }
+    }
+
+    fn field<'a>(entry: &'a RawEntry, name: &str) -> Option<&'a str> {
+        entry.iter().find(|(n, _)| n == name).map(|(_, v)| std::str::from_utf8(v).unwrap())
+    }
+
+    fn read_all(builder: Builder) -> Vec<RawEntry> {
+        let file = JournalFile::parse(builder.data).unwrap();
+        let offsets = file.entry_offsets().unwrap();
+        offsets.iter().map(|&offset| file.read_entry(offset).unwrap()).collect()
+    }
+
+    #[test]
+    fn regular_layout() {
+        let mut builder = Builder::new(false, 2);
+        builder.entry(1, 1_700_000_000_000_000, &[(0, b"MESSAGE=first"), (0, b"_SYSTEMD_UNIT=sshd.service")]);
+        builder.entry(2, 1_700_000_001_000_000, &[(0, b"MESSAGE=second"), (0, b"PRIORITY=3")]);
+        let entries = read_all(builder);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(field(&entries[0], "MESSAGE"), Some("first"));
+        assert_eq!(field(&entries[0], "_SYSTEMD_UNIT"), Some("sshd.service"));
+        assert_eq!(field(&entries[0], "__REALTIME_TIMESTAMP"), Some("1700000000000000"));
+        assert_eq!(field(&entries[0], "_BOOT_ID"), Some("11111111111111111111111111111111"));
+        assert!(field(&entries[0], "__CURSOR").unwrap().starts_with("s=abababababababababababababababab;i=1;"));
+        assert_eq!(field(&entries[1], "MESSAGE"), Some("second"));
+        assert_eq!(field(&entries[1], "PRIORITY"), Some("3"));
+    }
+
+    #[test]
+    fn compact_layout() {
+        let mut builder = Builder::new(true, 2);
+        builder.entry(1, 1_700_000_000_000_000, &[(0, b"MESSAGE=first")]);
+        builder.entry(2, 1_700_000_001_000_000, &[(0, b"MESSAGE=second"), (0, b"SYSLOG_IDENTIFIER=cron")]);
+        let entries = read_all(builder);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(field(&entries[0], "MESSAGE"), Some("first"));
+        assert_eq!(field(&entries[1], "SYSLOG_IDENTIFIER"), Some("cron"));
+        assert_eq!(field(&entries[1], "__MONOTONIC_TIMESTAMP"), Some("1700000000000000"));
+    }
+
+    #[test]
+    fn compressed_data_objects() {
+        let message = format!("MESSAGE={}", "a long repetitive message ".repeat(20));
+        let mut lz4 = (message.len() as u64).to_le_bytes().to_vec();
+        lz4.extend(lz4_flex::block::compress(message.as_bytes()));
+        let mut xz = Vec::new();
+        xz2::read::XzEncoder::new(b"SYSLOG_IDENTIFIER=app".as_slice(), 6).read_to_end(&mut xz).unwrap();
+
+        let mut builder = Builder::new(false, 1);
+        builder.entry(1, 1_700_000_000_000_000, &[(OBJECT_COMPRESSED_LZ4, &lz4), (OBJECT_COMPRESSED_XZ, &xz)]);
+        let entries = read_all(builder);
+        assert_eq!(field(&entries[0], "MESSAGE"), Some(&message["MESSAGE=".len()..]));
+        assert_eq!(field(&entries[0], "SYSLOG_IDENTIFIER"), Some("app"));
+    }
+
+    #[test]
+    fn truncated_last_entry() {
+        let mut builder = Builder::new(false, 2);
+        builder.entry(1, 1_700_000_000_000_000, &[(0, b"MESSAGE=complete")]);
+        builder.entry(2, 1_700_000_001_000_000, &[(0, b"MESSAGE=cut off")]);
+        builder.data.truncate(builder.data.len() - 12);
+
+        let file = JournalFile::parse(builder.data).unwrap();
+        let offsets = file.entry_offsets().unwrap();
+        assert_eq!(offsets.len(), 2);
+        let first = file.read_entry(offsets[0]).unwrap();
+        assert_eq!(field(&first, "MESSAGE"), Some("complete"));
+        assert!(file.read_entry(offsets[1]).is_err());
+    }
+}
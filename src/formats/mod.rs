@@ -0,0 +1,7 @@
+pub mod access_log;
+pub mod audit;
+pub mod container;
+pub mod custom;
+pub mod dmesg;
+pub mod export;
+pub mod journal_file;
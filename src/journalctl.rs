@@ -1,27 +1,185 @@
-use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer};
 use regex::Regex;
 use once_cell::sync::Lazy;
+use crate::analyzer::{Fields, LogEntry, local_to_micros, parse_time_zone, parse_timestamp_in_zone};
+use crate::analyzer::{json_payload, logfmt};
 
+/// "hostname service[pid]: message" — the part after the timestamp shared by BSD syslog
+/// and every `journalctl -o short-*` variant.
 static SYSLOG_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^([A-Za-z]{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})(?:\.\d+)?\s+(\S+)\s+([^\[:]+)(?:\[(\d+)\])?:\s*(.*)$").unwrap()
+    Regex::new(r"^(\S+)\s+([^\[:]+)(?:\[(\d+)\])?:\s*(.*)$").unwrap()
 });
 
+/// short / short-precise: "Oct 11 22:14:15[.123456] "
+static BSD_TIMESTAMP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([A-Za-z]{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2}(?:\.\d+)?)\s+").unwrap()
+});
+
+/// short-iso / short-iso-precise: "2026-10-11T22:14:15[.123456]+02:00 " (or "+0200", "Z")
+static ISO_TIMESTAMP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?)(Z|[+-]\d{2}:?\d{2})\s+").unwrap()
+});
+
+/// short-full: "Sun 2026-10-11 22:14:15[.123456] CEST "
+static FULL_TIMESTAMP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z]{3}\s+(\d{4}-\d{2}-\d{2}\s+\d{2}:\d{2}:\d{2}(?:\.\d+)?)\s+([A-Za-z][A-Za-z0-9+-]*)\s+").unwrap()
+});
+
+/// short-unix: "1697040000.123456 ", with-unix: "@1697040000.123456 " (followed by a short-full stamp)
+static UNIX_TIMESTAMP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(@?)(\d{9,}(?:\.\d+)?)\s+").unwrap()
+});
+
+/// short-monotonic: "[  123.456789] "
+static MONOTONIC_TIMESTAMP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[\s*(\d+(?:\.\d+)?)\]\s+").unwrap()
+});
+
+/// RFC 5424 header: "<PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID " followed by
+/// STRUCTURED-DATA and the optional MSG, which are split apart by `parse_structured_data`.
+static RFC5424_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^<(\d{1,3})>(\d{1,2}) (\S+) (\S+) (\S+) (\S+) (\S+) (.*)$").unwrap()
+});
+
+/// A field value as `journalctl -o json` encodes it: a string, `null` (value too large or
+/// omitted), an array of byte values for non-UTF-8/binary data, or an array of those when
+/// the field occurs more than once in the record.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "serde_json::Value")]
+pub enum JournalValue {
+    #[default]
+    Null,
+    Text(String),
+    Binary(Vec<u8>),
+    Multi(Vec<JournalValue>),
+}
+
+impl TryFrom<serde_json::Value> for JournalValue {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, String> {
+        use serde_json::Value;
+        Ok(match value {
+            Value::Null => JournalValue::Null,
+            Value::String(s) => JournalValue::Text(s),
+            // Repeated values are strings, nulls or byte arrays; numbers only appear as bytes
+            Value::Array(items) if items.iter().all(Value::is_number) => JournalValue::Binary(
+                items.iter()
+                    .map(|item| {
+                        item.as_u64()
+                            .and_then(|b| u8::try_from(b).ok())
+                            .ok_or_else(|| format!("invalid byte {} in binary field", item))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Array(items) if items.iter().any(Value::is_number) => {
+                return Err("binary field mixes bytes with other values".to_string());
+            }
+            Value::Array(items) => JournalValue::Multi(
+                items.into_iter().map(JournalValue::try_from).collect::<Result<_, _>>()?,
+            ),
+            // Not produced by journalctl, but keep numbers/bools/objects from other JSON sources
+            other => JournalValue::Text(other.to_string()),
+        })
+    }
+}
+
+impl JournalValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JournalValue::Null)
+    }
+
+    /// The value as text, decoding binary data lossily. Repeated values are joined by newlines.
+    pub fn text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            JournalValue::Null => None,
+            JournalValue::Text(s) => Some(Cow::Borrowed(s)),
+            JournalValue::Binary(bytes) => Some(String::from_utf8_lossy(bytes)),
+            JournalValue::Multi(values) => {
+                let parts: Vec<_> = values.iter().filter_map(JournalValue::text).collect();
+                Some(Cow::Owned(parts.join("\n")))
+            }
+        }
+    }
+
+    /// Raw bytes, if any part of the value arrived in binary (byte array) form.
+    pub fn binary(&self) -> Option<Cow<'_, [u8]>> {
+        match self {
+            JournalValue::Binary(bytes) => Some(Cow::Borrowed(bytes)),
+            JournalValue::Multi(values) if values.iter().any(|v| matches!(v, JournalValue::Binary(_))) => {
+                let mut bytes = Vec::new();
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        bytes.push(b'\n');
+                    }
+                    match value {
+                        JournalValue::Binary(b) => bytes.extend_from_slice(b),
+                        other => bytes.extend_from_slice(other.text().unwrap_or_default().as_bytes()),
+                    }
+                }
+                Some(Cow::Owned(bytes))
+            }
+            _ => None,
+        }
+    }
+
+    /// Each individual value as text (one per occurrence of a repeated field).
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        match self {
+            JournalValue::Multi(values) => values.iter().filter_map(JournalValue::text).collect(),
+            other => other.text().into_iter().collect(),
+        }
+    }
+}
+
+/// First value of `key` as text, removing it from the map.
+fn take_text(map: &mut BTreeMap<String, JournalValue>, key: &str) -> Option<String> {
+    map.remove(key)
+        .and_then(|value| value.values().into_iter().next().map(Cow::into_owned))
+}
+
+/// Deserialize a single-valued text field, tolerating the `null`, byte-array and
+/// repeated-value encodings (the first value wins).
+fn de_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = JournalValue::deserialize(deserializer)?;
+    Ok(value.values().into_iter().next().map(Cow::into_owned))
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct JournalEntry {
-    #[serde(rename = "__REALTIME_TIMESTAMP")]
+    #[serde(rename = "__REALTIME_TIMESTAMP", default, deserialize_with = "de_text")]
     pub realtime_timestamp: Option<String>,
 
-    #[serde(rename = "PRIORITY")]
+    #[serde(rename = "PRIORITY", default, deserialize_with = "de_text")]
     pub priority: Option<String>,
 
-    #[serde(rename = "SYSLOG_IDENTIFIER")]
+    #[serde(rename = "SYSLOG_FACILITY", default, deserialize_with = "de_text")]
+    pub facility: Option<String>,
+
+    #[serde(rename = "SYSLOG_IDENTIFIER", default, deserialize_with = "de_text")]
     pub syslog_identifier: Option<String>,
 
-    #[serde(rename = "_SYSTEMD_UNIT")]
+    #[serde(rename = "_SYSTEMD_UNIT", default, deserialize_with = "de_text")]
     pub systemd_unit: Option<String>,
 
-    #[serde(rename = "MESSAGE")]
-    pub message: Option<String>,
+    #[serde(rename = "MESSAGE", default)]
+    pub message: JournalValue,
+
+    /// Extra per-entry fields parsed from text formats (e.g. RFC 5424 header values and
+    /// structured-data params).
+    #[serde(skip)]
+    pub fields: Vec<(String, String)>,
+
+    /// The source gave no priority; it is guessed from the message by the priority rules
+    /// once the entry is converted (`priority` then only holds the fallback, if any).
+    #[serde(skip)]
+    pub priority_inferred: bool,
+
+    /// Every other field of a `journalctl -o json` record (`_PID`, `_HOSTNAME`, `__CURSOR`, ...).
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, JournalValue>,
 }
 
 impl JournalEntry {
@@ -32,6 +190,10 @@ impl JournalEntry {
             .unwrap_or(6)
     }
 
+    pub fn facility_num(&self) -> Option<u8> {
+        self.facility.as_ref().and_then(|f| f.parse().ok())
+    }
+
     pub fn service(&self) -> String {
         self.syslog_identifier
             .clone()
@@ -39,78 +201,720 @@ impl JournalEntry {
             .unwrap_or_else(|| "unknown".to_string())
     }
 
-    pub fn msg(&self) -> &str {
-        self.message.as_deref().unwrap_or("")
+    pub fn msg(&self) -> Cow<'_, str> {
+        self.message.text().unwrap_or_default()
     }
 
-    pub fn timestamp_secs(&self) -> Option<i64> {
+    pub fn timestamp_micros(&self) -> Option<i64> {
         self.realtime_timestamp
             .as_ref()
             .and_then(|ts| ts.parse::<i64>().ok())
-            .map(|us| us / 1_000_000)
     }
 
-    pub fn from_syslog_line(line: &str) -> Option<Self> {
-        let caps = SYSLOG_REGEX.captures(line)?;
+    pub fn to_log_entry(&self, line_num: usize) -> LogEntry {
+        let message = self.msg().into_owned();
+        let raw_message = self.message.binary().map(|bytes| bytes.into_owned().into_boxed_slice());
+        let mut fields = self.log_fields();
+        let mut priority = self.priority_num();
+        let mut priority_inferred = self.priority_inferred;
+
+        // Pull JSON or logfmt payloads out of the message; their level beats a default or
+        // guessed priority
+        if raw_message.is_none() {
+            let pairs = json_payload::flatten(&message)
+                .unwrap_or_else(|| logfmt::extract_pairs(&message));
+            if self.priority_is_default() && let Some(level) = logfmt::pairs_priority(&pairs) {
+                priority = level;
+                priority_inferred = false;
+            }
+            for (key, value) in &pairs {
+                fields.push(key, value);
+            }
+        }
+
+        LogEntry {
+            line_num,
+            timestamp_us: self.timestamp_micros(),
+            priority,
+            priority_inferred,
+            facility: self.facility_num(),
+            service: self.service(),
+            message,
+            raw_message,
+            fields,
+            source: None,
+            raw: false,
+        }
+    }
+
+    /// True when the priority carries no real information: missing, left to the rules,
+    /// or the fixed priority journald gives every line a service writes to stdout.
+    fn priority_is_default(&self) -> bool {
+        self.priority.is_none()
+            || self.priority_inferred
+            || self.extra.get("_TRANSPORT")
+                .and_then(JournalValue::text)
+                .is_some_and(|transport| transport == "stdout")
+    }
+
+    /// Build an entry from raw `NAME=value` journal fields, as stored in `.journal` files and
+    /// export streams. Non-UTF-8 values are kept as binary; repeated fields become multi-valued.
+    pub fn from_raw_fields(raw: Vec<(String, Vec<u8>)>) -> Self {
+        let mut map: BTreeMap<String, JournalValue> = BTreeMap::new();
+        for (name, bytes) in raw {
+            let value = match String::from_utf8(bytes) {
+                Ok(text) => JournalValue::Text(text),
+                Err(e) => JournalValue::Binary(e.into_bytes()),
+            };
+            let slot = map.entry(name).or_default();
+            *slot = match std::mem::take(slot) {
+                JournalValue::Null => value,
+                JournalValue::Multi(mut values) => {
+                    values.push(value);
+                    JournalValue::Multi(values)
+                }
+                single => JournalValue::Multi(vec![single, value]),
+            };
+        }
+
+        JournalEntry {
+            realtime_timestamp: take_text(&mut map, "__REALTIME_TIMESTAMP"),
+            priority: take_text(&mut map, "PRIORITY"),
+            facility: take_text(&mut map, "SYSLOG_FACILITY"),
+            syslog_identifier: take_text(&mut map, "SYSLOG_IDENTIFIER"),
+            systemd_unit: take_text(&mut map, "_SYSTEMD_UNIT"),
+            message: map.remove("MESSAGE").unwrap_or_default(),
+            fields: Vec::new(),
+            priority_inferred: false,
+            extra: map,
+        }
+    }
+
+    /// All fields not already promoted to LogEntry columns, for Row Detail and field filters.
+    fn log_fields(&self) -> Fields {
+        let mut fields = Fields::new();
+        // The unit is only used as the service name when SYSLOG_IDENTIFIER is missing
+        if let Some(ref unit) = self.systemd_unit {
+            fields.push("_SYSTEMD_UNIT", unit);
+        }
+        for (key, value) in &self.fields {
+            fields.push(key, value);
+        }
+        for (key, value) in &self.extra {
+            for v in value.values() {
+                fields.push(key, &v);
+            }
+        }
+        fields
+    }
+
+    pub fn from_syslog_line(line: &str, clock: &SyslogClock) -> Option<Self> {
+        if let Some(entry) = Self::from_rfc5424_line(line) {
+            return Some(entry);
+        }
 
-        let timestamp_str = caps.get(1)?.as_str();
-        let service = caps.get(3).map(|m| m.as_str().trim().to_string());
-        let message = caps.get(5).map(|m| m.as_str().to_string());
+        let (pri, line) = split_pri(line);
+        let (timestamp, rest) = split_short_timestamp(line, clock)?;
+        let caps = SYSLOG_REGEX.captures(rest)?;
 
-        let timestamp = parse_syslog_timestamp(timestamp_str);
-        let priority = infer_priority(message.as_deref().unwrap_or(""));
+        let service = caps.get(2).map(|m| m.as_str().trim().to_string());
+        let message = caps.get(4).map(|m| m.as_str().to_string());
+
+        let mut fields = Vec::new();
+        if let Some(original) = timestamp.original {
+            fields.push(("SYSLOG_TIMESTAMP".to_string(), original.to_string()));
+        }
+        if let Some(monotonic) = timestamp.monotonic_us {
+            fields.push(("__MONOTONIC_TIMESTAMP".to_string(), monotonic.to_string()));
+        }
 
         Some(JournalEntry {
-            realtime_timestamp: timestamp.map(|t| (t * 1_000_000).to_string()),
-            priority: Some(priority.to_string()),
+            realtime_timestamp: timestamp.realtime_us.map(|t| t.to_string()),
+            priority: pri.map(|pri| (pri & 7).to_string()),
+            facility: pri.map(|pri| (pri >> 3).to_string()),
             syslog_identifier: service,
             systemd_unit: None,
-            message,
+            message: message.map(JournalValue::Text).unwrap_or_default(),
+            fields,
+            priority_inferred: pri.is_none(),
+            extra: BTreeMap::new(),
+        })
+    }
+
+    /// Parse an RFC 5424 line:
+    /// "<165>1 2026-10-11T22:14:15.003Z host app 1234 ID47 [exampleSDID@32473 iut="3"] msg"
+    pub fn from_rfc5424_line(line: &str) -> Option<Self> {
+        let caps = RFC5424_REGEX.captures(line)?;
+
+        let pri: u8 = caps[1].parse().ok().filter(|&p| p <= MAX_PRI)?;
+        if &caps[2] != "1" {
+            return None;
+        }
+
+        let timestamp = nil_value(&caps[3]).and_then(|ts| {
+            chrono::DateTime::parse_from_rfc3339(ts)
+                .ok()
+                .map(|dt| dt.timestamp_micros())
+        });
+
+        let (sd_params, msg) = parse_structured_data(caps.get(8)?.as_str())?;
+
+        let mut fields = Vec::new();
+        if let Some(host) = nil_value(&caps[4]) {
+            fields.push(("_HOSTNAME".to_string(), host.to_string()));
+        }
+        if let Some(procid) = nil_value(&caps[6]) {
+            fields.push(("SYSLOG_PID".to_string(), procid.to_string()));
+        }
+        if let Some(msgid) = nil_value(&caps[7]) {
+            fields.push(("SYSLOG_MSGID".to_string(), msgid.to_string()));
+        }
+        fields.extend(sd_params);
+
+        // MSG may be prefixed with a UTF-8 BOM to mark it as Unicode
+        let message = msg.strip_prefix('\u{FEFF}').unwrap_or(msg).to_string();
+
+        Some(JournalEntry {
+            realtime_timestamp: timestamp.map(|t| t.to_string()),
+            priority: Some((pri & 7).to_string()),
+            facility: Some((pri >> 3).to_string()),
+            syslog_identifier: nil_value(&caps[5]).map(str::to_string),
+            systemd_unit: None,
+            message: JournalValue::Text(message),
+            fields,
+            priority_inferred: false,
+            extra: BTreeMap::new(),
         })
     }
 }
 
-fn parse_syslog_timestamp(ts: &str) -> Option<i64> {
-    let now = chrono::Local::now();
-    let year = now.format("%Y").to_string();
-    let full_ts = format!("{} {}", year, ts);
+/// Highest valid PRI value: facility 23 (local7) * 8 + severity 7.
+const MAX_PRI: u8 = 191;
+
+/// Strip a leading "<PRI>" (as sent on the wire or by `logger`) from an RFC 3164 line.
+/// Returns the decoded PRI, or None and the line unchanged if there is no valid prefix.
+fn split_pri(line: &str) -> (Option<u8>, &str) {
+    let Some(rest) = line.strip_prefix('<') else {
+        return (None, line);
+    };
+    let Some(end) = rest.find('>').filter(|&end| (1..=3).contains(&end)) else {
+        return (None, line);
+    };
+    match rest[..end].parse::<u8>() {
+        Ok(pri) if pri <= MAX_PRI => (Some(pri), &rest[end + 1..]),
+        _ => (None, line),
+    }
+}
+
+/// RFC 5424 uses "-" as the NILVALUE for absent header fields.
+fn nil_value(s: &str) -> Option<&str> {
+    if s == "-" { None } else { Some(s) }
+}
+
+/// Split RFC 5424 STRUCTURED-DATA from the trailing MSG.
+///
+/// Returns the params as ("SD-ID.PARAM-NAME", value) pairs, with `\"`, `\\` and `\]`
+/// escapes resolved, plus the remaining message text. Returns None if the
+/// structured data is malformed.
+fn parse_structured_data(s: &str) -> Option<(Vec<(String, String)>, &str)> {
+    if let Some(rest) = s.strip_prefix('-') {
+        return Some((Vec::new(), rest.strip_prefix(' ').unwrap_or(rest)));
+    }
+
+    let mut params = Vec::new();
+    let mut rest = s;
+
+    while let Some(element) = rest.strip_prefix('[') {
+        let id_end = element.find([' ', ']'])?;
+        let sd_id = &element[..id_end];
+        let mut cur = &element[id_end..];
+
+        loop {
+            cur = cur.trim_start_matches(' ');
+            if let Some(after) = cur.strip_prefix(']') {
+                cur = after;
+                break;
+            }
+
+            let eq = cur.find('=')?;
+            let name = &cur[..eq];
+            let value_part = cur[eq + 1..].strip_prefix('"')?;
+
+            let mut value = String::new();
+            let mut chars = value_part.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => match chars.next() {
+                        Some((_, esc @ ('"' | '\\' | ']'))) => value.push(esc),
+                        Some((_, other)) => {
+                            value.push('\\');
+                            value.push(other);
+                        }
+                        None => return None,
+                    },
+                    '"' => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+
+            params.push((format!("{}.{}", sd_id, name), value));
+            cur = &value_part[end? + 1..];
+        }
+
+        rest = cur;
+    }
+
+    if s.len() == rest.len() {
+        return None; // neither NILVALUE nor an SD-ELEMENT
+    }
 
-    // Treat as UTC so the display (also UTC) preserves the exact time written in the log.
-    // Syslog lines carry no per-line timezone, so converting via local timezone would shift
-    // the displayed time by the viewer's UTC offset.
-    chrono::NaiveDateTime::parse_from_str(&full_ts, "%Y %b %d %H:%M:%S")
+    Some((params, rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+/// Timestamp prefix of a syslog / `journalctl -o short-*` line.
+struct ShortTimestamp<'a> {
+    /// Wall-clock time in microseconds since the epoch (UTC).
+    realtime_us: Option<i64>,
+    /// Microseconds since boot (short-monotonic only).
+    monotonic_us: Option<i64>,
+    /// The timestamp as written, kept when it carries a zone that the UTC value no longer shows.
+    original: Option<&'a str>,
+}
+
+/// Recognise the timestamp prefix of every `journalctl -o short-*` variant and return it
+/// together with the rest of the line ("hostname service[pid]: message").
+fn split_short_timestamp<'a>(line: &'a str, clock: &SyslogClock) -> Option<(ShortTimestamp<'a>, &'a str)> {
+    if let Some(caps) = BSD_TIMESTAMP_REGEX.captures(line) {
+        let ts = ShortTimestamp {
+            realtime_us: clock.resolve(&caps[1]),
+            monotonic_us: None,
+            original: None,
+        };
+        return Some((ts, &line[caps[0].len()..]));
+    }
+
+    if let Some(caps) = ISO_TIMESTAMP_REGEX.captures(line) {
+        let offset = if &caps[2] == "Z" { "+0000" } else { &caps[2] };
+        let realtime_us = chrono::DateTime::parse_from_str(
+            &format!("{}{}", &caps[1], offset.replace(':', "")),
+            "%Y-%m-%dT%H:%M:%S%.f%z",
+        )
         .ok()
-        .map(|dt| dt.and_utc().timestamp())
+        .map(|dt| dt.timestamp_micros());
+        let ts = ShortTimestamp {
+            realtime_us,
+            monotonic_us: None,
+            original: caps.get(0).map(|m| m.as_str().trim_end()),
+        };
+        return Some((ts, &line[caps[0].len()..]));
+    }
+
+    if let Some((realtime_us, original, rest)) = split_full_timestamp(line, clock) {
+        let ts = ShortTimestamp { realtime_us, monotonic_us: None, original: Some(original) };
+        return Some((ts, rest));
+    }
+
+    if let Some(caps) = UNIX_TIMESTAMP_REGEX.captures(line) {
+        let mut rest = &line[caps[0].len()..];
+        let mut original = None;
+        // with-unix repeats the time as a short-full stamp after the "@seconds" prefix
+        if &caps[1] == "@" && let Some((_, full, after)) = split_full_timestamp(rest, clock) {
+            original = Some(full);
+            rest = after;
+        }
+        let ts = ShortTimestamp {
+            realtime_us: parse_decimal_micros(&caps[2]),
+            monotonic_us: None,
+            original,
+        };
+        return Some((ts, rest));
+    }
+
+    if let Some(caps) = MONOTONIC_TIMESTAMP_REGEX.captures(line) {
+        let ts = ShortTimestamp {
+            realtime_us: None,
+            monotonic_us: parse_decimal_micros(&caps[1]),
+            original: None,
+        };
+        return Some((ts, &line[caps[0].len()..]));
+    }
+
+    None
+}
+
+/// Parse a short-full stamp ("Sun 2026-10-11 22:14:15 CEST "). Returns the UTC time,
+/// the stamp as written and the rest of the line. A zone abbreviation that isn't known, or
+/// that names several zones, is taken to be the source's zone.
+fn split_full_timestamp<'a>(line: &'a str, clock: &SyslogClock) -> Option<(Option<i64>, &'a str, &'a str)> {
+    let caps = FULL_TIMESTAMP_REGEX.captures(line)?;
+    let local = chrono::NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%d %H:%M:%S%.f").ok();
+    let realtime_us = match tz_abbreviation_offset(&caps[2]) {
+        Some(offset) => local.map(|dt| dt.and_utc().timestamp_micros() - offset as i64 * 1_000_000),
+        None => local.and_then(|dt| local_to_micros(dt, &clock.tz)),
+    };
+    let stamp = caps.get(0)?.as_str();
+    Some((realtime_us, stamp.trim_end(), &line[stamp.len()..]))
+}
+
+/// UTC offset in seconds for the zone abbreviations short-full commonly prints. None for
+/// unknown ones and for CST, IST and BST, which each stand for more than one zone
+/// (China / US Central, India / Israel / Ireland, British Summer / Bangladesh).
+fn tz_abbreviation_offset(abbr: &str) -> Option<i32> {
+    let hours = match abbr {
+        "UTC" | "GMT" | "Z" | "WET" => 0,
+        "CET" | "WEST" => 1,
+        "CEST" | "EET" => 2,
+        "EEST" | "MSK" => 3,
+        "JST" | "KST" => 9,
+        "AEST" => 10,
+        "AEDT" => 11,
+        "EST" | "CDT" => -5,
+        "EDT" => -4,
+        "MST" => -7,
+        "MDT" => -6,
+        "PST" => -8,
+        "PDT" => -7,
+        _ => return None,
+    };
+    Some(hours * 3600)
+}
+
+/// Parse "seconds[.fraction]" into microseconds, keeping up to six fractional digits.
+/// None if the seconds don't fit, as with an epoch in nanoseconds.
+pub fn parse_decimal_micros(s: &str) -> Option<i64> {
+    let (secs, frac) = s.split_once('.').unwrap_or((s, ""));
+    let secs: i64 = secs.parse().ok()?;
+    let frac: String = frac.chars().chain(std::iter::repeat('0')).take(6).collect();
+    secs.checked_mul(1_000_000)?.checked_add(frac.parse::<i64>().ok()?)
 }
 
-fn infer_priority(msg: &str) -> u8 {
-    let msg_lower = msg.to_lowercase();
+/// How one source's BSD syslog stamps ("Oct 11 22:14:15"), which carry neither a year nor
+/// a zone, are turned into absolute times.
+#[derive(Clone, Debug)]
+pub struct SyslogClock {
+    /// Newest time the source can contain, in epoch microseconds: the file's mtime, or
+    /// None for live sources (now), whose parser then moves it along with their entries.
+    /// Stamps that would land after it are from the previous year, so a December log read
+    /// in January, or one spanning New Year, stays in order.
+    pub anchor_us: Option<i64>,
+    /// Year chosen by the user, overriding inference.
+    pub year: Option<i32>,
+    /// Zone the source wrote its local times in.
+    pub tz: jiff::tz::TimeZone,
+    /// Wall-clock time of boot in epoch microseconds, which turns dmesg's seconds since
+    /// boot into absolute times.
+    pub boot_us: Option<i64>,
+}
+
+impl Default for SyslogClock {
+    fn default() -> Self {
+        Self {
+            anchor_us: None,
+            year: None,
+            tz: jiff::tz::TimeZone::UTC,
+            boot_us: None,
+        }
+    }
+}
+
+impl SyslogClock {
+    /// Build from the year / time zone / boot time boxes of the open and SSH dialogs
+    /// (empty = automatic / UTC / unknown). The boot time is read in the zone.
+    pub fn from_settings(year: &str, tz: &str, boot: &str) -> Result<Self, String> {
+        let year = match year.trim() {
+            "" => None,
+            text => Some(
+                text.parse::<i32>()
+                    .ok()
+                    .filter(|y| (1970..=9999).contains(y))
+                    .ok_or_else(|| format!("Invalid year: {}", text))?,
+            ),
+        };
+        let tz = parse_time_zone(tz)?;
+        let boot_us = match boot.trim() {
+            "" => None,
+            text => Some(parse_timestamp_in_zone(text, &tz).ok_or_else(|| format!("Invalid boot time: {}", text))?),
+        };
+        Ok(Self {
+            anchor_us: None,
+            year,
+            tz,
+            boot_us,
+        })
+    }
+
+    /// Parse a BSD syslog stamp ("Oct 11 22:14:15[.123456]") into microseconds since the epoch.
+    fn resolve(&self, stamp: &str) -> Option<i64> {
+        self.resolve_yearless(stamp, "%b %d %H:%M:%S%.f")
+    }
+
+    /// Parse `stamp` with a chrono format string. Stamps without a zone are read in `tz`,
+    /// and stamps without a year get one inferred as for BSD syslog.
+    pub fn resolve_format(&self, stamp: &str, format: &str) -> Option<i64> {
+        const YEAR_SPECIFIERS: [&str; 7] = ["%Y", "%y", "%G", "%F", "%D", "%c", "%s"];
+        if !YEAR_SPECIFIERS.iter().any(|spec| format.contains(spec)) {
+            return self.resolve_yearless(stamp, format);
+        }
+        if let Ok(dt) = chrono::DateTime::parse_from_str(stamp, format) {
+            return Some(dt.timestamp_micros());
+        }
+        chrono::NaiveDateTime::parse_from_str(stamp, format)
+            .ok()
+            .and_then(|dt| local_to_micros(dt, &self.tz))
+    }
+
+    fn resolve_yearless(&self, stamp: &str, format: &str) -> Option<i64> {
+        let format = format!("%Y {}", format);
+        let at_year = |year: i32| {
+            chrono::NaiveDateTime::parse_from_str(&format!("{} {}", year, stamp), &format)
+                .ok()
+                .and_then(|dt| local_to_micros(dt, &self.tz))
+        };
+        if let Some(year) = self.year {
+            return at_year(year);
+        }
+
+        let anchor_us = self.anchor_us.unwrap_or_else(|| chrono::Utc::now().timestamp_micros());
+        // The anchor's year as the source's clock showed it
+        let anchor_year = jiff::Timestamp::from_microsecond(anchor_us)
+            .ok()?
+            .to_zoned(self.tz.clone())
+            .year() as i32;
+        // A day of slack covers clocks running slightly ahead of the anchor
+        const SLACK_US: i64 = 86_400 * 1_000_000;
+        match at_year(anchor_year) {
+            Some(us) if us <= anchor_us + SLACK_US => Some(us),
+            // Later in the year than the anchor (or Feb 29 of a non-leap year): previous year
+            _ => at_year(anchor_year - 1),
+        }
+    }
+}
 
-    if msg_lower.contains("panic") || msg_lower.contains("fatal") || msg_lower.contains("critical") {
-        return 2;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2023-10-11 16:00:00 UTC
+    const TIME_US: i64 = 1_697_040_000_000_000;
+
+    fn syslog(line: &str, clock: &SyslogClock) -> LogEntry {
+        JournalEntry::from_syslog_line(line, clock)
+            .unwrap_or_else(|| panic!("not parsed: {}", line))
+            .to_log_entry(1)
     }
 
-    if msg_lower.contains("error") || msg_lower.contains("failed") || msg_lower.contains("failure")
-        || msg_lower.contains("cannot") || msg_lower.contains("unable to")
-        || msg_lower.contains("segfault") || msg_lower.contains("exception")
-    {
-        return 3;
+    fn clock_in(tz: &str) -> SyslogClock {
+        SyslogClock::from_settings("2023", tz, "").unwrap()
     }
 
-    if msg_lower.contains("warning") || msg_lower.contains("warn")
-        || msg_lower.contains("timeout") || msg_lower.contains("timed out")
-        || msg_lower.contains("retrying") || msg_lower.contains("deprecated")
-        || msg_lower.contains("denied") || msg_lower.contains("refused")
-    {
-        return 4;
+    #[test]
+    fn pri_prefix() {
+        let entry = syslog("<34>Oct 11 16:00:00 host su[1]: 'su root' failed", &clock_in(""));
+        assert_eq!((entry.facility, entry.priority), (Some(4), 2));
+        assert!(!entry.priority_inferred);
+        assert_eq!(entry.timestamp_us, Some(TIME_US));
+        assert_eq!(entry.service, "su");
+
+        // Out of range or not a number: not a PRI, so the line isn't syslog at all
+        assert_eq!(split_pri("<192>Oct 11 16:00:00 host su[1]: x"), (None, "<192>Oct 11 16:00:00 host su[1]: x"));
+        assert_eq!(split_pri("<abc>Oct 11 16:00:00 host su[1]: x"), (None, "<abc>Oct 11 16:00:00 host su[1]: x"));
+        assert_eq!(split_pri("<1234>x"), (None, "<1234>x"));
+        assert!(JournalEntry::from_syslog_line("<192>Oct 11 16:00:00 host su[1]: x", &clock_in("")).is_none());
+
+        let entry = syslog("Oct 11 16:00:00 host su[1]: 'su root' failed", &clock_in(""));
+        assert_eq!(entry.facility, None);
+        assert!(entry.priority_inferred);
     }
 
-    if msg_lower.contains("started") || msg_lower.contains("stopped")
-        || msg_lower.contains("connected") || msg_lower.contains("disconnected")
-        || msg_lower.contains("loaded") || msg_lower.contains("finished")
-    {
-        return 5;
+    fn rfc5424(line: &str) -> LogEntry {
+        JournalEntry::from_rfc5424_line(line)
+            .unwrap_or_else(|| panic!("not parsed: {}", line))
+            .to_log_entry(1)
     }
 
-    6
+    #[test]
+    fn structured_data_escapes() {
+        let (params, msg) = parse_structured_data(r#"[id@1 q="say \"hi\"" b="a\]b" s="c:\\dir" o="\n" raw="x]y"] msg"#).unwrap();
+        assert_eq!(params, [
+            ("id@1.q".to_string(), r#"say "hi""#.to_string()),
+            ("id@1.b".to_string(), "a]b".to_string()),
+            ("id@1.s".to_string(), r"c:\dir".to_string()),
+            // Other escapes are kept as written
+            ("id@1.o".to_string(), r"\n".to_string()),
+            ("id@1.raw".to_string(), "x]y".to_string()),
+        ]);
+        assert_eq!(msg, "msg");
+    }
+
+    #[test]
+    fn structured_data_elements() {
+        let (params, msg) = parse_structured_data(r#"[a@1 x="1"][b@2 y="2" z=""][meta] text [not sd]"#).unwrap();
+        assert_eq!(params, [
+            ("a@1.x".to_string(), "1".to_string()),
+            ("b@2.y".to_string(), "2".to_string()),
+            ("b@2.z".to_string(), String::new()),
+        ]);
+        assert_eq!(msg, "text [not sd]");
+
+        assert_eq!(parse_structured_data("- text"), Some((Vec::new(), "text")));
+        assert_eq!(parse_structured_data("-"), Some((Vec::new(), "")));
+        assert_eq!(parse_structured_data("text"), None);
+        assert_eq!(parse_structured_data(r#"[a@1 x="open"#), None);
+        assert_eq!(parse_structured_data(r#"[a@1 x=1]"#), None);
+    }
+
+    #[test]
+    fn rfc5424_lines() {
+        let entry = rfc5424(r#"<165>1 2023-10-11T16:00:00.003Z host app 1234 ID47 [origin@1 ip="10.0.0.1"] started"#);
+        assert_eq!(entry.timestamp_us, Some(TIME_US + 3_000));
+        assert_eq!((entry.priority, entry.facility), (5, Some(20)));
+        assert_eq!(entry.service, "app");
+        assert_eq!(entry.message, "started");
+        assert_eq!(entry.fields.get_all("_HOSTNAME").next(), Some("host"));
+        assert_eq!(entry.fields.get_all("SYSLOG_PID").next(), Some("1234"));
+        assert_eq!(entry.fields.get_all("SYSLOG_MSGID").next(), Some("ID47"));
+        assert_eq!(entry.fields.get_all("origin@1.ip").next(), Some("10.0.0.1"));
+
+        // Nil header fields are left out; a BOM before MSG is dropped
+        let entry = rfc5424("<14>1 - - - - - - \u{FEFF}h\u{e9}llo");
+        assert_eq!(entry.timestamp_us, None);
+        assert_eq!(entry.message, "h\u{e9}llo");
+        assert_eq!(entry.fields.get_all("_HOSTNAME").next(), None);
+        assert_eq!(entry.fields.get_all("SYSLOG_PID").next(), None);
+        assert_eq!(entry.fields.get_all("SYSLOG_MSGID").next(), None);
+
+        assert!(JournalEntry::from_rfc5424_line("<14>2 - - - - - - msg").is_none());
+        assert!(JournalEntry::from_rfc5424_line("<192>1 - - - - - - msg").is_none());
+        assert!(JournalEntry::from_rfc5424_line("<14>1 - - - - - msg").is_none());
+    }
+
+    #[test]
+    fn short() {
+        let entry = syslog("Oct 11 16:00:00 host sshd[42]: Accepted publickey", &clock_in(""));
+        assert_eq!(entry.timestamp_us, Some(TIME_US));
+        assert_eq!(entry.service, "sshd");
+        assert_eq!(entry.message, "Accepted publickey");
+    }
+
+    #[test]
+    fn short_precise() {
+        let entry = syslog("Oct 11 18:00:00.123456 host sshd[42]: hello", &clock_in("Europe/Berlin"));
+        assert_eq!(entry.timestamp_us, Some(TIME_US + 123_456));
+    }
+
+    #[test]
+    fn short_iso() {
+        let clock = clock_in("");
+        assert_eq!(syslog("2023-10-11T18:00:00+0200 host cron[1]: run", &clock).timestamp_us, Some(TIME_US));
+        assert_eq!(syslog("2023-10-11T16:00:00Z host cron[1]: run", &clock).timestamp_us, Some(TIME_US));
+    }
+
+    #[test]
+    fn short_iso_precise() {
+        let entry = syslog("2023-10-11T12:00:00.500000-04:00 host cron[1]: run", &clock_in(""));
+        assert_eq!(entry.timestamp_us, Some(TIME_US + 500_000));
+    }
+
+    #[test]
+    fn short_full() {
+        let clock = clock_in("Asia/Kolkata");
+        assert_eq!(syslog("Wed 2023-10-11 18:00:00 CEST host app[7]: up", &clock).timestamp_us, Some(TIME_US));
+        assert_eq!(syslog("Wed 2023-10-11 16:00:00.25 UTC host app[7]: up", &clock).timestamp_us, Some(TIME_US + 250_000));
+        // Ambiguous or unknown abbreviations are read in the source's zone
+        assert_eq!(syslog("Wed 2023-10-11 21:30:00 IST host app[7]: up", &clock).timestamp_us, Some(TIME_US));
+        assert_eq!(syslog("Wed 2023-10-11 21:30:00 XYZ host app[7]: up", &clock).timestamp_us, Some(TIME_US));
+    }
+
+    #[test]
+    fn short_monotonic() {
+        let entry = syslog("[  123.456789] host kernel: usb 1-1: new device", &clock_in(""));
+        assert_eq!(entry.timestamp_us, None);
+        assert_eq!(entry.fields.get_all("__MONOTONIC_TIMESTAMP").next(), Some("123456789"));
+        assert_eq!(entry.service, "kernel");
+    }
+
+    #[test]
+    fn short_unix() {
+        let entry = syslog("1697040000.123456 host sshd[42]: hello", &clock_in(""));
+        assert_eq!(entry.timestamp_us, Some(TIME_US + 123_456));
+        // A number too short to be a time is not one
+        assert!(JournalEntry::from_syslog_line("42 host sshd[42]: hello", &clock_in("")).is_none());
+    }
+
+    #[test]
+    fn over_long_seconds_are_no_time() {
+        assert_eq!(parse_decimal_micros("1697040000123456789"), None);
+        assert_eq!(parse_decimal_micros("9223372036854.775808"), None);
+        assert_eq!(parse_decimal_micros("9223372036854.775807"), Some(i64::MAX));
+        // An epoch in nanoseconds still parses as a line, just without a time
+        let entry = syslog("1697040000123456789 host sshd[42]: hello", &clock_in(""));
+        assert_eq!(entry.timestamp_us, None);
+        assert_eq!(entry.message, "hello");
+    }
+
+    #[test]
+    fn with_unix() {
+        let entry = syslog("@1697040000.5 Wed 2023-10-11 16:00:00 UTC host sshd[42]: hello", &clock_in(""));
+        assert_eq!(entry.timestamp_us, Some(TIME_US + 500_000));
+        assert_eq!(entry.fields.get_all("SYSLOG_TIMESTAMP").next(), Some("Wed 2023-10-11 16:00:00 UTC"));
+        assert_eq!(entry.message, "hello");
+    }
+
+    fn value(json: &str) -> Result<JournalValue, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn byte_arrays() {
+        assert!(matches!(value("[104, 105, 0, 255]"), Ok(JournalValue::Binary(bytes)) if bytes == [104, 105, 0, 255]));
+        assert!(matches!(value(r#"["a", [104, 105], null]"#), Ok(JournalValue::Multi(values)) if values.len() == 3));
+        assert!(value("[104, 256]").is_err());
+        assert!(value("[104, -1]").is_err());
+        assert!(value("[104, 1.5]").is_err());
+        assert!(value(r#"[104, "i"]"#).is_err());
+        assert!(value(r#"["a", [104, 300]]"#).is_err());
+    }
+
+    fn utc_us(stamp: &str) -> i64 {
+        chrono::DateTime::parse_from_rfc3339(stamp).unwrap().timestamp_micros()
+    }
+
+    fn anchored(anchor: &str) -> SyslogClock {
+        SyslogClock { anchor_us: Some(utc_us(anchor)), ..SyslogClock::default() }
+    }
+
+    #[test]
+    fn resolve_takes_the_anchor_year() {
+        let clock = anchored("2026-10-17T12:00:00Z");
+        assert_eq!(clock.resolve("Oct 17 11:00:00"), Some(utc_us("2026-10-17T11:00:00Z")));
+        assert_eq!(clock.resolve("Jan  1 00:00:00.5"), Some(utc_us("2026-01-01T00:00:00.5Z")));
+        // A day of slack for a clock running ahead of the anchor
+        assert_eq!(clock.resolve("Oct 18 06:00:00"), Some(utc_us("2026-10-18T06:00:00Z")));
+    }
+
+    #[test]
+    fn resolve_rolls_back_after_the_anchor() {
+        // A December log read in January
+        let clock = anchored("2027-01-02T08:00:00Z");
+        assert_eq!(clock.resolve("Dec 31 23:59:59"), Some(utc_us("2026-12-31T23:59:59Z")));
+        assert_eq!(clock.resolve("Jan  1 00:00:01"), Some(utc_us("2027-01-01T00:00:01Z")));
+        assert_eq!(clock.resolve("Jan  9 00:00:00"), Some(utc_us("2026-01-09T00:00:00Z")));
+        // Feb 29 only exists in the year before
+        let clock = anchored("2025-03-01T00:00:00Z");
+        assert_eq!(clock.resolve("Feb 29 10:00:00"), Some(utc_us("2024-02-29T10:00:00Z")));
+    }
+
+    #[test]
+    fn resolve_uses_the_set_year_and_zone() {
+        let clock = SyslogClock { anchor_us: Some(utc_us("2027-01-02T08:00:00Z")), ..clock_in("Europe/Berlin") };
+        assert_eq!(clock.resolve("Dec 31 23:59:59"), Some(utc_us("2023-12-31T22:59:59Z")));
+        // The anchor's year is the one the source's clock showed
+        let clock = SyslogClock { year: None, ..clock };
+        let clock = SyslogClock { anchor_us: Some(utc_us("2026-12-31T23:30:00Z")), ..clock };
+        assert_eq!(clock.resolve("Jan  1 00:15:00"), Some(utc_us("2026-12-31T23:15:00Z")));
+    }
 }
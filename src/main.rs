@@ -1,6 +1,7 @@
 mod analyzer;
 mod app;
 mod background;
+mod formats;
 mod journalctl;
 mod ui;
 mod workers;
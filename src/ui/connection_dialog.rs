@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 use eframe::egui;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crate::formats::custom;
+use crate::journalctl::SyslogClock;
+use crate::ui::open_file_dialog::format_combo;
+use crate::workers::parse_options::ParseOptions;
 use crate::workers::ssh_reader::{SshConfig, AuthMethod};
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -14,6 +18,14 @@ pub struct ConnectionProfile {
     command: String,
     #[serde(default)]
     password: String,
+    #[serde(default)]
+    year: String,
+    #[serde(default)]
+    timezone: String,
+    #[serde(default)]
+    format: String,
+    #[serde(default)]
+    boot_time: String,
 }
 
 fn profiles_path() -> PathBuf {
@@ -41,7 +53,9 @@ fn save_profiles(profiles: &[ConnectionProfile]) {
     }
 }
 
-pub fn config_for_profile(name: &str) -> Option<crate::workers::ssh_reader::SshConfig> {
+/// SSH settings of the profile called `name`, with a note about any setting that had to be
+/// dropped.
+pub fn config_for_profile(name: &str) -> Option<(crate::workers::ssh_reader::SshConfig, Option<String>)> {
     let profile = load_profiles().into_iter().find(|p| p.name == name)?;
     let auth = match profile.auth_choice {
         0 => {
@@ -54,13 +68,32 @@ pub fn config_for_profile(name: &str) -> Option<crate::workers::ssh_reader::SshC
         1 => crate::workers::ssh_reader::AuthMethod::KeyFile(std::path::PathBuf::from(&profile.key_path)),
         _ => crate::workers::ssh_reader::AuthMethod::Agent,
     };
-    Some(crate::workers::ssh_reader::SshConfig {
+    let (parse_options, warning) = profile_parse_options(&profile);
+    let config = crate::workers::ssh_reader::SshConfig {
         host: profile.host,
         port: profile.port,
         username: profile.username,
         auth,
         command: profile.command,
-    })
+        parse_options,
+    };
+    Some((config, warning))
+}
+
+/// Parse options saved with a profile, falling back to defaults for settings that no
+/// longer validate (e.g. a format removed from formats.json), and what was dropped.
+fn profile_parse_options(profile: &ConnectionProfile) -> (ParseOptions, Option<String>) {
+    let (clock, clock_error) = match SyslogClock::from_settings(&profile.year, &profile.timezone, &profile.boot_time) {
+        Ok(clock) => (clock, None),
+        Err(e) => (SyslogClock::default(), Some(e)),
+    };
+    let (options, format_error) = match ParseOptions::new(clock.clone(), &profile.format) {
+        Ok(options) => (options, None),
+        Err(e) => (ParseOptions::new(clock, "").unwrap_or_default(), Some(format!("{} - auto-detecting", e))),
+    };
+    let errors: Vec<String> = clock_error.into_iter().chain(format_error).collect();
+    let warning = (!errors.is_empty()).then(|| format!("Profile '{}': {}", profile.name, errors.join("; ")));
+    (options, warning)
 }
 
 pub struct ConnectionDialog {
@@ -72,7 +105,16 @@ pub struct ConnectionDialog {
     pub password: String,
     pub key_path: String,
     pub command: String,
+    /// Year and zone for the host's syslog stamps (empty = current year / UTC)
+    pub year: String,
+    pub timezone: String,
+    /// When the host booted, for dmesg stamps (empty = unknown)
+    pub boot_time: String,
+    /// Built-in format id or name from formats.json (empty = detect)
+    pub format: String,
     pub error: Option<String>,
+    /// User format names, read from formats.json when the dialog opens
+    format_names: Option<Vec<String>>,
     profiles: Vec<ConnectionProfile>,
     selected_profile: Option<usize>,
     prev_selected_profile: Option<usize>,
@@ -90,7 +132,12 @@ impl Default for ConnectionDialog {
             password: String::new(),
             key_path: String::new(),
             command: "journalctl -o json --no-pager -n 10000 -f".to_string(),
+            year: String::new(),
+            timezone: String::new(),
+            boot_time: String::new(),
+            format: String::new(),
             error: None,
+            format_names: None,
             profiles: load_profiles(),
             selected_profile: None,
             prev_selected_profile: None,
@@ -108,6 +155,10 @@ impl ConnectionDialog {
             self.auth_choice = profile.auth_choice;
             self.key_path = profile.key_path.clone();
             self.command = profile.command.clone();
+            self.year = profile.year.clone();
+            self.timezone = profile.timezone.clone();
+            self.format = profile.format.clone();
+            self.boot_time = profile.boot_time.clone();
             self.password = BASE64.decode(&profile.password)
                 .ok()
                 .and_then(|bytes| String::from_utf8(bytes).ok())
@@ -122,8 +173,10 @@ impl ConnectionDialog {
         let mut should_close = false;
 
         if !self.open {
+            self.format_names = None;
             return None;
         }
+        let format_names = self.format_names.take().unwrap_or_else(custom::format_names);
 
         egui::Window::new("SSH Connection")
             .collapsible(false)
@@ -186,6 +239,10 @@ impl ConnectionDialog {
                                 key_path: self.key_path.clone(),
                                 command: self.command.clone(),
                                 password: BASE64.encode(self.password.as_bytes()),
+                                year: self.year.trim().to_string(),
+                                timezone: self.timezone.trim().to_string(),
+                                format: self.format.clone(),
+                                boot_time: self.boot_time.trim().to_string(),
                             };
                             // Update existing or add new
                             if let Some(pos) = self.profiles.iter().position(|p| p.name == profile.name) {
@@ -228,6 +285,22 @@ impl ConnectionDialog {
                         ui.label("Username:");
                         ui.text_edit_singleline(&mut self.username);
                         ui.end_row();
+
+                        ui.label("Syslog year:");
+                        ui.add(egui::TextEdit::singleline(&mut self.year).hint_text("auto"));
+                        ui.end_row();
+
+                        ui.label("Time zone:");
+                        ui.add(egui::TextEdit::singleline(&mut self.timezone).hint_text("UTC, local or e.g. Europe/Berlin"));
+                        ui.end_row();
+
+                        ui.label("Boot time:");
+                        ui.add(egui::TextEdit::singleline(&mut self.boot_time).hint_text("for dmesg, e.g. 2026-10-16 08:00:00"));
+                        ui.end_row();
+
+                        ui.label("Log format:");
+                        format_combo(ui, "ssh_format", &mut self.format, &format_names);
+                        ui.end_row();
                     });
 
                 ui.separator();
@@ -292,6 +365,7 @@ impl ConnectionDialog {
                 });
             });
 
+        self.format_names = Some(format_names);
         if should_close {
             self.open = false;
         }
@@ -318,6 +392,8 @@ impl ConnectionDialog {
             return Err("Username is required".to_string());
         }
         let port: u16 = self.port.parse().map_err(|_| "Invalid port number".to_string())?;
+        let clock = SyslogClock::from_settings(&self.year, &self.timezone, &self.boot_time)?;
+        let parse_options = ParseOptions::new(clock, &self.format)?;
 
         let auth = match self.auth_choice {
             0 => AuthMethod::Password(self.password.clone()),
@@ -336,6 +412,7 @@ impl ConnectionDialog {
             username: self.username.trim().to_string(),
             auth,
             command: self.command.clone(),
+            parse_options,
         })
     }
 }
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use eframe::egui;
-use crate::analyzer::{FilterCriteria, CombineMode};
+use crate::analyzer::{FilterCriteria, CombineMode, format_field_filter, parse_field_filters, parse_timestamp_in_zone};
+use crate::ui::log_viewer::facility_label;
 
 #[derive(Clone)]
 pub struct FilterBar {
@@ -9,8 +10,21 @@ pub struct FilterBar {
     pub pattern_valid: bool,
     pub pattern2_valid: bool,
     pub selected_services: HashSet<String>,
+    pub selected_facilities: HashSet<u8>,
+    pub selected_sources: HashSet<String>,
+    pub selected_statuses: HashSet<u16>,
     pub priority_choice: usize,   // index into PRIORITY_LABELS
+    pub time_from_text: String,
+    pub time_to_text: String,
+    pub time_from_valid: bool,
+    pub time_to_valid: bool,
+    /// Zone the time boxes are typed in; follows the display zone.
+    pub time_zone: jiff::tz::TimeZone,
+    pub field_text: String,
+    pub field_valid: bool,
     pub combine_mode: CombineMode,
+    /// Show lines no format matched, as raw entries
+    pub show_raw: bool,
 }
 
 const PRIORITY_LABELS: &[&str] = &[
@@ -50,8 +64,19 @@ impl Default for FilterBar {
             pattern_valid: true,
             pattern2_valid: true,
             selected_services: HashSet::new(),
+            selected_facilities: HashSet::new(),
+            selected_sources: HashSet::new(),
+            selected_statuses: HashSet::new(),
             priority_choice: 0,
+            time_from_text: String::new(),
+            time_to_text: String::new(),
+            time_from_valid: true,
+            time_to_valid: true,
+            time_zone: jiff::tz::TimeZone::UTC,
+            field_text: String::new(),
+            field_valid: true,
             combine_mode: CombineMode::Match,
+            show_raw: true,
         }
     }
 }
@@ -62,8 +87,15 @@ impl FilterBar {
         !self.pattern_text.is_empty()
             || !self.pattern2_text.is_empty()
             || !self.selected_services.is_empty()
+            || !self.selected_facilities.is_empty()
+            || !self.selected_sources.is_empty()
+            || !self.selected_statuses.is_empty()
             || self.priority_choice != 0
+            || !self.time_from_text.is_empty()
+            || !self.time_to_text.is_empty()
+            || !self.field_text.is_empty()
             || self.combine_mode != CombineMode::Match
+            || !self.show_raw
     }
 
     /// Reconstruct a FilterCriteria from the bar's current state.
@@ -76,12 +108,47 @@ impl FilterBar {
             filter.set_pattern2(&self.pattern2_text);
         }
         filter.units = self.selected_services.clone();
+        filter.facilities = self.selected_facilities.clone();
+        filter.sources = self.selected_sources.clone();
+        filter.statuses = self.selected_statuses.clone();
         filter.max_priority = priority_max(self.priority_choice);
+        filter.time_from = parse_timestamp_in_zone(&self.time_from_text, &self.time_zone);
+        filter.time_to = parse_timestamp_in_zone(&self.time_to_text, &self.time_zone);
+        filter.field_filters = parse_field_filters(&self.field_text).unwrap_or_default();
         filter.combine_mode = self.combine_mode;
+        filter.show_raw = self.show_raw;
+    }
+
+    /// Append a `key=value` condition (from Row Detail) to the field filter.
+    pub fn add_field_filter(&mut self, key: &str, value: &str, filter: &mut FilterCriteria) {
+        if !self.field_text.trim().is_empty() {
+            self.field_text.push(' ');
+        }
+        self.field_text.push_str(&format_field_filter(key, value));
+        self.set_field_text(filter);
+    }
+
+    fn set_field_text(&mut self, filter: &mut FilterCriteria) -> bool {
+        match parse_field_filters(&self.field_text) {
+            Ok(filters) => {
+                filter.field_filters = filters;
+                self.field_valid = true;
+            }
+            Err(_) => self.field_valid = false,
+        }
+        self.field_valid
     }
 
     /// Show filter bar UI. Returns true if filter changed.
-    pub fn show(&mut self, ui: &mut egui::Ui, services: &[String], filter: &mut FilterCriteria) -> bool {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        services: &[String],
+        facilities: &[u8],
+        sources: &[String],
+        statuses: &[u16],
+        filter: &mut FilterCriteria,
+    ) -> bool {
         let mut changed = false;
 
         ui.horizontal(|ui| {
@@ -167,6 +234,128 @@ impl FilterBar {
                     }
                 });
 
+            // Facility multi-select (only sources with a real <PRI> or SYSLOG_FACILITY have one)
+            if !facilities.is_empty() || !self.selected_facilities.is_empty() {
+                ui.label("Facility:");
+                let label = if self.selected_facilities.is_empty() {
+                    "All".to_string()
+                } else if self.selected_facilities.len() == 1 {
+                    facility_label(*self.selected_facilities.iter().next().unwrap()).to_string()
+                } else {
+                    format!("{} selected", self.selected_facilities.len())
+                };
+                egui::ComboBox::from_id_salt("facility_filter")
+                    .selected_text(&label)
+                    .width(100.0)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(self.selected_facilities.is_empty(), "All").clicked() {
+                            self.selected_facilities.clear();
+                            filter.facilities.clear();
+                            changed = true;
+                        }
+                        for &fac in facilities {
+                            let mut selected = self.selected_facilities.contains(&fac);
+                            if ui.checkbox(&mut selected, facility_label(fac)).changed() {
+                                if selected {
+                                    self.selected_facilities.insert(fac);
+                                } else {
+                                    self.selected_facilities.remove(&fac);
+                                }
+                                filter.facilities = self.selected_facilities.clone();
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+
+            // Source file multi-select (only when several files are merged)
+            if !sources.is_empty() || !self.selected_sources.is_empty() {
+                ui.label("Source:");
+                let label = if self.selected_sources.is_empty() {
+                    "All".to_string()
+                } else if self.selected_sources.len() == 1 {
+                    self.selected_sources.iter().next().unwrap().clone()
+                } else {
+                    format!("{} selected", self.selected_sources.len())
+                };
+                egui::ComboBox::from_id_salt("source_filter")
+                    .selected_text(&label)
+                    .width(150.0)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(self.selected_sources.is_empty(), "All").clicked() {
+                            self.selected_sources.clear();
+                            filter.sources.clear();
+                            changed = true;
+                        }
+                        for src in sources {
+                            let mut selected = self.selected_sources.contains(src);
+                            if ui.checkbox(&mut selected, src).changed() {
+                                if selected {
+                                    self.selected_sources.insert(src.clone());
+                                } else {
+                                    self.selected_sources.remove(src);
+                                }
+                                filter.sources = self.selected_sources.clone();
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+
+            // HTTP status multi-select (only for access logs)
+            if !statuses.is_empty() || !self.selected_statuses.is_empty() {
+                ui.label("Status:");
+                let label = if self.selected_statuses.is_empty() {
+                    "All".to_string()
+                } else if self.selected_statuses.len() == 1 {
+                    self.selected_statuses.iter().next().unwrap().to_string()
+                } else {
+                    format!("{} selected", self.selected_statuses.len())
+                };
+                egui::ComboBox::from_id_salt("status_filter")
+                    .selected_text(&label)
+                    .width(90.0)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(self.selected_statuses.is_empty(), "All").clicked() {
+                            self.selected_statuses.clear();
+                            filter.statuses.clear();
+                            changed = true;
+                        }
+                        // One toggle per class, then the individual codes
+                        for class in 1u16..=5 {
+                            let codes: Vec<u16> = statuses.iter().copied().filter(|s| s / 100 == class).collect();
+                            if codes.is_empty() {
+                                continue;
+                            }
+                            let mut selected = codes.iter().all(|s| self.selected_statuses.contains(s));
+                            if ui.checkbox(&mut selected, format!("{}xx", class)).changed() {
+                                for code in codes {
+                                    if selected {
+                                        self.selected_statuses.insert(code);
+                                    } else {
+                                        self.selected_statuses.remove(&code);
+                                    }
+                                }
+                                filter.statuses = self.selected_statuses.clone();
+                                changed = true;
+                            }
+                        }
+                        ui.separator();
+                        for &status in statuses {
+                            let mut selected = self.selected_statuses.contains(&status);
+                            if ui.checkbox(&mut selected, status.to_string()).changed() {
+                                if selected {
+                                    self.selected_statuses.insert(status);
+                                } else {
+                                    self.selected_statuses.remove(&status);
+                                }
+                                filter.statuses = self.selected_statuses.clone();
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+
             // Priority combo
             ui.label("Priority:");
             egui::ComboBox::from_id_salt("priority_filter")
@@ -180,6 +369,14 @@ impl FilterBar {
                         }
                     }
                 });
+
+            if ui.checkbox(&mut self.show_raw, "Raw lines")
+                .on_hover_text("Show lines that matched no log format, as they are")
+                .changed()
+            {
+                filter.show_raw = self.show_raw;
+                changed = true;
+            }
         });
 
         // Quick pattern buttons row
@@ -192,12 +389,64 @@ impl FilterBar {
                     changed = true;
                 }
             }
+
+            ui.separator();
+
+            // Field conditions (journal fields, structured data, ...)
+            ui.label("Fields:");
+            let color = if self.field_valid { egui::Color32::WHITE } else { egui::Color32::RED };
+            let resp = ui.add(
+                egui::TextEdit::singleline(&mut self.field_text)
+                    .desired_width(200.0)
+                    .text_color(color)
+                    .hint_text("_PID=1234 _TRANSPORT!=kernel http.status>=500"),
+            );
+            if resp.changed() && self.set_field_text(filter) {
+                changed = true;
+            }
+
+            ui.separator();
+
+            // Time range in the display zone; an empty box leaves that end open
+            ui.label("Time:");
+            for (text, valid, bound, hint) in [
+                (&mut self.time_from_text, &mut self.time_from_valid, &mut filter.time_from, "from"),
+                (&mut self.time_to_text, &mut self.time_to_valid, &mut filter.time_to, "to"),
+            ] {
+                let color = if *valid { egui::Color32::WHITE } else { egui::Color32::RED };
+                let resp = ui.add(
+                    egui::TextEdit::singleline(text)
+                        .desired_width(150.0)
+                        .text_color(color)
+                        .hint_text(format!("{} YYYY-MM-DD HH:MM:SS", hint)),
+                );
+                if resp.changed() {
+                    let parsed = parse_timestamp_in_zone(text, &self.time_zone);
+                    *valid = text.trim().is_empty() || parsed.is_some();
+                    if *valid {
+                        *bound = parsed;
+                        changed = true;
+                    }
+                }
+            }
+
+            ui.separator();
             if ui.small_button("Clear").clicked() {
                 self.pattern_text.clear();
                 self.pattern2_text.clear();
                 self.selected_services.clear();
+                self.selected_facilities.clear();
+                self.selected_sources.clear();
+                self.selected_statuses.clear();
                 self.priority_choice = 0;
+                self.time_from_text.clear();
+                self.time_to_text.clear();
+                self.time_from_valid = true;
+                self.time_to_valid = true;
+                self.field_text.clear();
+                self.field_valid = true;
                 self.combine_mode = CombineMode::Match;
+                self.show_raw = true;
                 *filter = FilterCriteria::default();
                 self.pattern_valid = true;
                 self.pattern2_valid = true;
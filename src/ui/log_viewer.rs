@@ -1,5 +1,6 @@
 use eframe::egui;
-use crate::analyzer::{LogStore, LogEntry, FilterCriteria};
+use jiff::tz::TimeZone;
+use crate::analyzer::{LogStore, LogEntry, FilterCriteria, micros_in_zone, parse_time_zone, zone_label};
 
 pub fn priority_color(priority: u8) -> egui::Color32 {
     match priority {
@@ -15,7 +16,7 @@ pub fn priority_color(priority: u8) -> egui::Color32 {
 fn format_entry_for_copy(entry: &LogEntry) -> String {
     format!(
         "{} {}[{}]: {}",
-        entry.timestamp,
+        entry.timestamp_text(),
         entry.service,
         priority_label(entry.priority),
         entry.message,
@@ -36,6 +37,138 @@ pub fn priority_label(priority: u8) -> &'static str {
     }
 }
 
+const INFERRED_HINT: &str = "The source gave no priority; it was inferred from the message by the priority rules";
+
+/// Priority column text: the label, with a `?` when the source didn't give the priority,
+/// or `???` for a raw line, whose priority is unknown.
+pub fn entry_priority_label(entry: &LogEntry) -> String {
+    if entry.raw {
+        "???".to_string()
+    } else if entry.priority_inferred {
+        format!("{}?", priority_label(entry.priority))
+    } else {
+        priority_label(entry.priority).to_string()
+    }
+}
+
+pub fn facility_label(facility: u8) -> &'static str {
+    match facility {
+        0 => "kern",
+        1 => "user",
+        2 => "mail",
+        3 => "daemon",
+        4 => "auth",
+        5 => "syslog",
+        6 => "lpr",
+        7 => "news",
+        8 => "uucp",
+        9 => "cron",
+        10 => "authpriv",
+        11 => "ftp",
+        12 => "ntp",
+        13 => "security",
+        14 => "console",
+        15 => "clock",
+        16 => "local0",
+        17 => "local1",
+        18 => "local2",
+        19 => "local3",
+        20 => "local4",
+        21 => "local5",
+        22 => "local6",
+        23 => "local7",
+        _ => "???",
+    }
+}
+
+/// How the Time column renders `LogEntry::timestamp_us`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeFormat {
+    Seconds,
+    Millis,
+    Micros,
+    /// Signed offset from the selected row's timestamp.
+    Relative,
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 4] = [TimeFormat::Seconds, TimeFormat::Millis, TimeFormat::Micros, TimeFormat::Relative];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeFormat::Seconds => "Seconds",
+            TimeFormat::Millis => "Milliseconds",
+            TimeFormat::Micros => "Microseconds",
+            TimeFormat::Relative => "Relative to selected row",
+        }
+    }
+
+    fn column_width(self) -> f32 {
+        match self {
+            TimeFormat::Seconds | TimeFormat::Relative => 160.0,
+            TimeFormat::Millis => 185.0,
+            TimeFormat::Micros => 210.0,
+        }
+    }
+}
+
+/// Zone timestamps are displayed in (View > Time Zone).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DisplayZone {
+    Utc,
+    /// The viewer's own zone.
+    Local,
+    /// The zone set for the open files or SSH profile, i.e. the servers' wall clock.
+    Source,
+    /// An IANA zone typed by the user.
+    Named(String),
+}
+
+/// Render a timestamp in `tz` for display. `reference` is the selected row's timestamp, used by
+/// `TimeFormat::Relative`; without one, relative mode falls back to absolute microseconds.
+pub fn format_time(timestamp_us: Option<i64>, format: TimeFormat, reference: Option<i64>, tz: &TimeZone) -> String {
+    let Some(us) = timestamp_us else {
+        return String::new();
+    };
+    let pattern = match format {
+        TimeFormat::Seconds => "%Y-%m-%d %H:%M:%S",
+        TimeFormat::Millis => "%Y-%m-%d %H:%M:%S%.3f",
+        TimeFormat::Micros => "%Y-%m-%d %H:%M:%S%.6f",
+        TimeFormat::Relative => match reference {
+            Some(reference) => return format_relative(us - reference),
+            None => "%Y-%m-%d %H:%M:%S%.6f",
+        },
+    };
+    micros_in_zone(us, tz)
+        .map(|dt| dt.format(pattern).to_string())
+        .unwrap_or_default()
+}
+
+/// The time column for `entry`: its wall-clock time, or for kernel entries whose boot
+/// time is unknown, the seconds since boot as dmesg prints them.
+pub fn format_entry_time(entry: &LogEntry, format: TimeFormat, reference: Option<i64>, tz: &TimeZone) -> String {
+    if entry.timestamp_us.is_none()
+        && let Some(monotonic_us) = entry.fields.get_all("__MONOTONIC_TIMESTAMP").next().and_then(|v| v.parse::<i64>().ok())
+    {
+        return format!("[{:5}.{:06}]", monotonic_us / 1_000_000, monotonic_us % 1_000_000);
+    }
+    format_time(entry.timestamp_us, format, reference, tz)
+}
+
+/// "+00:01:02.003004", with a day count prefix for offsets of a day or more.
+fn format_relative(delta_us: i64) -> String {
+    let sign = if delta_us < 0 { '-' } else { '+' };
+    let abs = delta_us.unsigned_abs();
+    let micros = abs % 1_000_000;
+    let secs = abs / 1_000_000;
+    let (days, h, m, s) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}{}d {:02}:{:02}:{:02}.{:06}", sign, days, h, m, s, micros)
+    } else {
+        format!("{}{:02}:{:02}:{:02}.{:06}", sign, h, m, s, micros)
+    }
+}
+
 pub struct LogViewer {
     pub auto_scroll: bool,
     /// Index into LogStore.entries of the selected row, or None.
@@ -48,8 +181,25 @@ pub struct LogViewer {
     pub show_in_context_requested: bool,
     /// Entry index to toggle bookmark for; consumed by app.
     pub toggle_bookmark_requested: Option<usize>,
+    /// (key, value) of a Row Detail field to add as a field filter; consumed by app.
+    pub filter_field_requested: Option<(String, String)>,
+    pub time_format: TimeFormat,
+    /// Selected entry's timestamp, refreshed each frame for `TimeFormat::Relative`.
+    time_reference: Option<i64>,
+    /// Whether the Source column is shown (several files merged), refreshed each frame.
+    show_source: bool,
+    /// Entry fields shown as extra columns between Service and Message.
+    pub field_columns: Vec<String>,
+    display_zone: DisplayZone,
+    /// Zone configured for the current source (Open File or SSH profile).
+    source_zone: TimeZone,
+    /// `display_zone` resolved.
+    time_zone: TimeZone,
 }
 
+const SOURCE_COLUMN_WIDTH: f32 = 140.0;
+const FIELD_COLUMN_WIDTH: f32 = 110.0;
+
 impl Default for LogViewer {
     fn default() -> Self {
         Self {
@@ -60,10 +210,31 @@ impl Default for LogViewer {
             scroll_to_row: None,
             show_in_context_requested: false,
             toggle_bookmark_requested: None,
+            filter_field_requested: None,
+            time_format: TimeFormat::Seconds,
+            time_reference: None,
+            show_source: false,
+            field_columns: Vec::new(),
+            display_zone: DisplayZone::Utc,
+            source_zone: TimeZone::UTC,
+            time_zone: TimeZone::UTC,
         }
     }
 }
 
+/// Classic hex dump: offset, 16 hex bytes, printable ASCII.
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk.iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<47}  |{}|\n", i * 16, hex.join(" "), ascii));
+    }
+    out
+}
+
 /// Try to pretty-format a JSON string. Returns None if not valid JSON.
 fn try_pretty_json(s: &str) -> Option<String> {
     let trimmed = s.trim();
@@ -75,6 +246,43 @@ fn try_pretty_json(s: &str) -> Option<String> {
 }
 
 impl LogViewer {
+    pub fn display_zone(&self) -> &DisplayZone {
+        &self.display_zone
+    }
+
+    /// Zone timestamps are currently rendered in.
+    pub fn time_zone(&self) -> &TimeZone {
+        &self.time_zone
+    }
+
+    pub fn set_display_zone(&mut self, zone: DisplayZone) -> Result<(), String> {
+        self.time_zone = match &zone {
+            DisplayZone::Utc => TimeZone::UTC,
+            DisplayZone::Local => TimeZone::system(),
+            DisplayZone::Source => self.source_zone.clone(),
+            DisplayZone::Named(name) => parse_time_zone(name)?,
+        };
+        self.display_zone = zone;
+        Ok(())
+    }
+
+    /// Show `key` as a column, or hide it if it already is one.
+    pub fn toggle_field_column(&mut self, key: &str) {
+        if let Some(pos) = self.field_columns.iter().position(|c| c == key) {
+            self.field_columns.remove(pos);
+        } else {
+            self.field_columns.push(key.to_string());
+        }
+    }
+
+    /// Record the zone of a newly opened source, used by `DisplayZone::Source`.
+    pub fn set_source_zone(&mut self, tz: TimeZone) {
+        self.source_zone = tz;
+        if self.display_zone == DisplayZone::Source {
+            self.time_zone = self.source_zone.clone();
+        }
+    }
+
     pub fn notify_new_entries(&mut self, count: usize) {
         if !self.auto_scroll && !self.is_at_bottom {
             self.new_entry_count += count;
@@ -112,6 +320,10 @@ impl LogViewer {
 
         let row_height = 18.0;
         let total_rows = filtered_indices.len();
+        self.time_reference = self.selected_entry
+            .and_then(|idx| store.entries.get(idx))
+            .and_then(|entry| entry.timestamp_us);
+        self.show_source = !store.sources.is_empty();
 
         if total_rows == 0 {
             ui.centered_and_justified(|ui| {
@@ -150,29 +362,93 @@ impl LogViewer {
                                     .num_columns(2)
                                     .spacing([10.0, 4.0])
                                     .show(ui, |ui| {
+                                        if let Some(ref source) = entry.source {
+                                            ui.label(egui::RichText::new("Source:").strong());
+                                            ui.label(egui::RichText::new(&**source).monospace());
+                                            ui.end_row();
+                                        }
+
                                         ui.label(egui::RichText::new("Line:").strong());
                                         ui.label(egui::RichText::new(format!("{}", entry.line_num)).monospace());
                                         ui.end_row();
 
                                         ui.label(egui::RichText::new("Timestamp:").strong());
-                                        ui.label(egui::RichText::new(&entry.timestamp).monospace());
+                                        let mut time = format_entry_time(entry, TimeFormat::Micros, None, &self.time_zone);
+                                        if entry.timestamp_us.is_some() {
+                                            time = format!("{} {}", time, zone_label(&self.time_zone));
+                                        }
+                                        ui.label(egui::RichText::new(time).monospace());
                                         ui.end_row();
 
                                         ui.label(egui::RichText::new("Priority:").strong());
-                                        ui.label(egui::RichText::new(priority_label(entry.priority))
+                                        let mut priority = priority_label(entry.priority).to_string();
+                                        if entry.raw {
+                                            priority = "unknown (the line matched no log format)".to_string();
+                                        } else if entry.priority_inferred {
+                                            priority.push_str(" (inferred from the message)");
+                                        }
+                                        ui.label(egui::RichText::new(priority)
                                             .monospace()
                                             .color(priority_color(entry.priority)));
                                         ui.end_row();
 
+                                        if let Some(facility) = entry.facility {
+                                            ui.label(egui::RichText::new("Facility:").strong());
+                                            ui.label(egui::RichText::new(format!("{} ({})", facility_label(facility), facility)).monospace());
+                                            ui.end_row();
+                                        }
+
                                         ui.label(egui::RichText::new("Service:").strong());
                                         ui.label(egui::RichText::new(&entry.service)
                                             .monospace()
                                             .color(egui::Color32::from_rgb(130, 200, 255)));
                                         ui.end_row();
+
+                                        for (key, value) in entry.fields.iter() {
+                                            ui.label(egui::RichText::new(format!("{}:", key)).strong());
+                                            ui.horizontal(|ui| {
+                                                if ui.small_button("=")
+                                                    .on_hover_text(format!("Filter on {}", key))
+                                                    .clicked()
+                                                {
+                                                    self.filter_field_requested = Some((key.to_string(), value.to_string()));
+                                                }
+                                                let is_column = self.field_columns.iter().any(|c| c == key);
+                                                if ui.small_button(if is_column { "-col" } else { "+col" })
+                                                    .on_hover_text(format!("{} {} as a column", if is_column { "Hide" } else { "Show" }, key))
+                                                    .clicked()
+                                                {
+                                                    self.toggle_field_column(key);
+                                                }
+                                                ui.add(
+                                                    egui::Label::new(egui::RichText::new(value).monospace())
+                                                        .wrap_mode(egui::TextWrapMode::Wrap)
+                                                        .selectable(true),
+                                                );
+                                            });
+                                            ui.end_row();
+                                        }
                                     });
 
                                 ui.separator();
-                                ui.label(egui::RichText::new("Message:").strong());
+                                if let Some(ref raw) = entry.raw_message {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("Message:").strong());
+                                        ui.label(egui::RichText::new(format!("binary, {} bytes (shown as lossy UTF-8)", raw.len()))
+                                            .color(egui::Color32::from_rgb(255, 160, 60)));
+                                    });
+                                    egui::CollapsingHeader::new("Raw bytes (hex)")
+                                        .id_salt("raw_message_hex")
+                                        .show(ui, |ui| {
+                                            ui.add(
+                                                egui::Label::new(egui::RichText::new(hex_dump(raw)).monospace())
+                                                    .wrap_mode(egui::TextWrapMode::Extend)
+                                                    .selectable(true),
+                                            );
+                                        });
+                                } else {
+                                    ui.label(egui::RichText::new("Message:").strong());
+                                }
 
                                 if let Some(pretty) = try_pretty_json(&entry.message) {
                                     ui.add(
@@ -206,19 +482,36 @@ impl LogViewer {
             .show(ui, |ui| {
                 // Header
                 ui.horizontal(|ui| {
-                    let widths = [60.0, 160.0, 60.0, 150.0];
+                    let widths = [60.0, self.time_format.column_width(), 60.0, 150.0];
                     ui.add_sized([widths[0], row_height], egui::Label::new(
                         egui::RichText::new("Line#").strong().monospace(),
                     ));
                     ui.add_sized([widths[1], row_height], egui::Label::new(
-                        egui::RichText::new("Time").strong().monospace(),
+                        egui::RichText::new(format!("Time ({})", zone_label(&self.time_zone))).strong().monospace(),
                     ));
                     ui.add_sized([widths[2], row_height], egui::Label::new(
                         egui::RichText::new("Pri").strong().monospace(),
                     ));
+                    if self.show_source {
+                        ui.add_sized([SOURCE_COLUMN_WIDTH, row_height], egui::Label::new(
+                            egui::RichText::new("Source").strong().monospace(),
+                        ));
+                    }
                     ui.add_sized([widths[3], row_height], egui::Label::new(
                         egui::RichText::new("Service").strong().monospace(),
                     ));
+                    let mut remove_column = None;
+                    for key in &self.field_columns {
+                        let header = ui.add_sized([FIELD_COLUMN_WIDTH, row_height], egui::Label::new(
+                            egui::RichText::new(key).strong().monospace(),
+                        ).truncate().sense(egui::Sense::click()));
+                        if header.on_hover_text("Click to remove this column").clicked() {
+                            remove_column = Some(key.clone());
+                        }
+                    }
+                    if let Some(key) = remove_column {
+                        self.toggle_field_column(&key);
+                    }
                     ui.label(egui::RichText::new("Message").strong().monospace());
                 });
 
@@ -331,8 +624,9 @@ impl LogViewer {
         is_context_highlight: bool,
         is_bookmarked: bool,
     ) -> egui::Response {
-        let pri_color = priority_color(entry.priority);
-        let widths = [60.0, 160.0, 60.0, 150.0];
+        // Raw lines have no priority; show them dimmed like DEBUG
+        let pri_color = priority_color(if entry.raw { 7 } else { entry.priority });
+        let widths = [60.0, self.time_format.column_width(), 60.0, 150.0];
 
         let row_resp = ui.horizontal(|ui| {
             let (line_text, line_color) = if is_bookmarked {
@@ -347,16 +641,29 @@ impl LogViewer {
             ));
 
             ui.add_sized([widths[1], row_height], egui::Label::new(
-                egui::RichText::new(&entry.timestamp)
+                egui::RichText::new(format_entry_time(entry, self.time_format, self.time_reference, &self.time_zone))
                     .monospace()
                     .color(egui::Color32::from_rgb(180, 180, 180)),
             ));
 
-            ui.add_sized([widths[2], row_height], egui::Label::new(
-                egui::RichText::new(priority_label(entry.priority))
+            let pri_resp = ui.add_sized([widths[2], row_height], egui::Label::new(
+                egui::RichText::new(entry_priority_label(entry))
                     .monospace()
                     .color(pri_color),
             ));
+            if entry.raw {
+                pri_resp.on_hover_text("The line matched no log format and is shown as it is");
+            } else if entry.priority_inferred {
+                pri_resp.on_hover_text(INFERRED_HINT);
+            }
+
+            if self.show_source {
+                ui.add_sized([SOURCE_COLUMN_WIDTH, row_height], egui::Label::new(
+                    egui::RichText::new(entry.source.as_deref().unwrap_or(""))
+                        .monospace()
+                        .color(egui::Color32::from_rgb(200, 170, 230)),
+                ).truncate());
+            }
 
             ui.add_sized([widths[3], row_height], egui::Label::new(
                 egui::RichText::new(&entry.service)
@@ -364,12 +671,37 @@ impl LogViewer {
                     .color(egui::Color32::from_rgb(130, 200, 255)),
             ));
 
+            for key in &self.field_columns {
+                ui.add_sized([FIELD_COLUMN_WIDTH, row_height], egui::Label::new(
+                    egui::RichText::new(entry.fields.get_all(key).next().unwrap_or(""))
+                        .monospace()
+                        .color(egui::Color32::from_rgb(170, 210, 170)),
+                ).truncate());
+            }
+
+            if entry.raw_message.is_some() {
+                ui.label(
+                    egui::RichText::new("BIN")
+                        .monospace()
+                        .small()
+                        .color(egui::Color32::BLACK)
+                        .background_color(egui::Color32::from_rgb(255, 160, 60)),
+                )
+                .on_hover_text("Binary message, decoded lossily \u{2014} see Row Detail for hex");
+            }
+
+            // Rows show only the first line of multi-line entries; Row Detail has the rest
+            let (first_line, extra_lines) = match entry.message.split_once('\n') {
+                Some((first, rest)) => (first, rest.lines().count()),
+                None => (entry.message.as_str(), 0),
+            };
+
             // Message with regex highlighting (filter = orange, find = green)
             let has_filter = filter.pattern.is_some();
             let has_find = find_pattern.is_some();
 
             if has_filter || has_find {
-                let msg = &entry.message;
+                let msg = first_line;
                 let mut job = egui::text::LayoutJob::default();
 
                 // Collect all highlight spans: (start, end, is_find)
@@ -443,11 +775,19 @@ impl LogViewer {
                 ui.add(egui::Label::new(job).wrap_mode(egui::TextWrapMode::Extend));
             } else {
                 ui.add(egui::Label::new(
-                    egui::RichText::new(&entry.message)
+                    egui::RichText::new(first_line)
                         .monospace()
                         .color(egui::Color32::from_rgb(220, 220, 220)),
                 ).wrap_mode(egui::TextWrapMode::Extend));
             }
+
+            if extra_lines > 0 {
+                ui.label(
+                    egui::RichText::new(format!("\u{21B5} +{} lines", extra_lines))
+                        .monospace()
+                        .color(egui::Color32::from_rgb(120, 120, 120)),
+                );
+            }
         });
 
         // Make the whole row rect clickable and paint background
@@ -2,4 +2,5 @@ pub mod connection_dialog;
 pub mod filter_bar;
 pub mod log_viewer;
 pub mod open_file_dialog;
+pub mod priority_rules_dialog;
 pub mod save_settings;
@@ -1,27 +1,91 @@
 use eframe::egui;
+use crate::formats::custom;
+use crate::journalctl::SyslogClock;
+use crate::workers::file_reader;
+use crate::workers::line_parser::LineFormat;
+use crate::workers::multiline::MultilineRules;
+use crate::workers::parse_options::ParseOptions;
 
 pub struct OpenFileDialog {
     pub open: bool,
     pub path: String,
     pub error: Option<String>,
+    /// Multi-line continuation rules applied to the next file opened
+    pub fold_leading_whitespace: bool,
+    pub fold_unmatched: bool,
+    pub start_pattern: String,
+    /// Keep reading lines appended to the file after loading (tail -F)
+    pub follow: bool,
+    /// Year and zone for syslog stamps without them (empty = infer from mtime / UTC)
+    pub year: String,
+    pub timezone: String,
+    /// When the machine booted, for dmesg stamps (empty = from the log, if it says)
+    pub boot_time: String,
+    /// Format to parse with: a built-in format id or a name from formats.json (empty = detect)
+    pub format: String,
+    /// User format names, read from formats.json when the dialog opens
+    format_names: Option<Vec<String>>,
 }
 
 impl Default for OpenFileDialog {
     fn default() -> Self {
+        let rules = MultilineRules::default();
         Self {
             open: false,
             path: String::new(),
             error: None,
+            fold_leading_whitespace: rules.leading_whitespace,
+            fold_unmatched: rules.unmatched_lines,
+            start_pattern: String::new(),
+            follow: false,
+            year: String::new(),
+            timezone: String::new(),
+            boot_time: String::new(),
+            format: String::new(),
+            format_names: None,
         }
     }
 }
 
 impl OpenFileDialog {
-    /// Show the dialog. Returns Some(path) when user clicks Open with a valid path.
-    pub fn show(&mut self, ctx: &egui::Context) -> Option<String> {
+    /// Build the continuation rules from the dialog's current settings.
+    pub fn multiline_rules(&self) -> Result<MultilineRules, String> {
+        let start_pattern = if self.start_pattern.trim().is_empty() {
+            None
+        } else {
+            let re = regex::Regex::new(self.start_pattern.trim())
+                .map_err(|e| format!("Invalid start-of-entry regex: {}", e))?;
+            Some(re)
+        };
+        Ok(MultilineRules {
+            leading_whitespace: self.fold_leading_whitespace,
+            unmatched_lines: self.fold_unmatched,
+            start_pattern,
+        })
+    }
+
+    /// How to parse the files: log format and the year / zone for syslog stamps.
+    pub fn parse_options(&self) -> Result<ParseOptions, String> {
+        let clock = SyslogClock::from_settings(&self.year, &self.timezone, &self.boot_time)?;
+        ParseOptions::new(clock, &self.format)
+    }
+
+    /// Paths typed in the dialog; several files or directories are separated by `;`.
+    fn paths(&self) -> Vec<String> {
+        self.path
+            .split(';')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect()
+    }
+
+    /// Show the dialog. Returns the paths when user clicks Open and they all exist.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Vec<String>> {
         if !self.open {
+            self.format_names = None;
             return None;
         }
+        let format_names = self.format_names.take().unwrap_or_else(custom::format_names);
 
         let mut result = None;
         let mut should_close = false;
@@ -33,17 +97,18 @@ impl OpenFileDialog {
             .show(ctx, |ui| {
                 ui.set_min_width(500.0);
 
-                ui.label("Enter the path to a log file:");
+                ui.label("Enter the path to a log file or directory (separate several with ;):");
                 ui.add_space(4.0);
 
                 let resp = ui.add(
                     egui::TextEdit::singleline(&mut self.path)
                         .desired_width(480.0)
-                        .hint_text("/var/log/syslog"),
+                        .hint_text("/var/log/syslog; /var/log/syslog.1; /var/log/syslog.2.gz"),
                 );
 
-                // Auto-focus the text field when dialog opens
-                if resp.gained_focus() || self.error.is_none() {
+                // Auto-focus the text field when dialog opens (without stealing focus
+                // from the multi-line settings below)
+                if self.error.is_none() && ui.memory(|m| m.focused().is_none()) {
                     resp.request_focus();
                 }
 
@@ -60,30 +125,104 @@ impl OpenFileDialog {
                 // Try native dialog as secondary option
                 ui.horizontal(|ui| {
                     if ui.button("Browse...").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
-                            .set_title("Open Log File")
-                            .add_filter("Log files", &["log", "txt", "json"])
+                        if let Some(paths) = rfd::FileDialog::new()
+                            .set_title("Open Log Files")
+                            .add_filter("Log files", &["log", "txt", "json", "journal", "export", "gz", "xz", "zst", "bz2"])
                             .add_filter("All files", &["*"])
-                            .pick_file()
+                            .pick_files()
                         {
-                            self.path = path.to_string_lossy().to_string();
+                            self.path = paths
+                                .iter()
+                                .map(|p| p.to_string_lossy().to_string())
+                                .collect::<Vec<_>>()
+                                .join("; ");
                         }
                     }
+                    if ui.button("Folder...").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .set_title("Open Log Directory")
+                            .pick_folder()
+                    {
+                        self.path = path.to_string_lossy().to_string();
+                    }
                     ui.label("(may not work on all systems)");
                 });
 
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    format_combo(ui, "open_file_format", &mut self.format, &format_names);
+                });
+                ui.checkbox(&mut self.follow, "Follow: keep reading new lines, reopening after log rotation (single file)");
+
+                egui::CollapsingHeader::new("Multi-line entries")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label("Fold these lines into the previous entry (stack traces, wrapped messages):");
+                        ui.checkbox(&mut self.fold_leading_whitespace, "Lines starting with whitespace");
+                        ui.checkbox(&mut self.fold_unmatched, "Lines that don't match the log format");
+                        ui.horizontal(|ui| {
+                            ui.label("New entry starts with:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.start_pattern)
+                                    .desired_width(250.0)
+                                    .hint_text(r"regex, e.g. ^\d{4}-\d{2}-\d{2}"),
+                            );
+                        });
+                        ui.small("When set, every line not matching the regex is a continuation.");
+                    });
+
+                egui::CollapsingHeader::new("Timestamps")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label("For syslog stamps like \"Oct 11 22:14:15\" that carry no year or zone:");
+                        egui::Grid::new("open_timestamps").num_columns(2).show(ui, |ui| {
+                            ui.label("Year:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.year)
+                                    .desired_width(80.0)
+                                    .hint_text("auto"),
+                            );
+                            ui.end_row();
+                            ui.label("Time zone:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.timezone)
+                                    .desired_width(200.0)
+                                    .hint_text("UTC, local or e.g. Europe/Berlin"),
+                            );
+                            ui.end_row();
+                            ui.label("Boot time:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.boot_time)
+                                    .desired_width(200.0)
+                                    .hint_text("from the log"),
+                            )
+                            .on_hover_text("For dmesg's seconds since boot, e.g. 2026-10-16 08:00:00 (in the time zone above)");
+                            ui.end_row();
+                        });
+                        ui.small("By default the year is inferred from the file's modification time.");
+                    });
+
                 ui.add_space(4.0);
                 ui.separator();
 
                 ui.horizontal(|ui| {
                     if ui.button("Open").clicked() || enter_pressed {
-                        let path = self.path.trim().to_string();
-                        if path.is_empty() {
+                        let paths = self.paths();
+                        if paths.is_empty() {
                             self.error = Some("Path is required".to_string());
-                        } else if !std::path::Path::new(&path).exists() {
-                            self.error = Some(format!("File not found: {}", path));
+                        } else if let Some(missing) = paths.iter().find(|p| !std::path::Path::new(p).exists()) {
+                            self.error = Some(format!("File not found: {}", missing));
+                        } else if let Err(e) = self.multiline_rules().and(self.parse_options()) {
+                            self.error = Some(e);
+                        } else if self.follow
+                            && (paths.len() > 1 || std::path::Path::new(&paths[0]).is_dir())
+                        {
+                            self.error = Some("Follow works on a single file".to_string());
+                        } else if self.follow && !file_reader::is_followable(&paths[0]) {
+                            self.error = Some("Follow doesn't work on .journal or export files".to_string());
                         } else {
-                            result = Some(path);
+                            result = Some(paths);
                             should_close = true;
                         }
                     }
@@ -93,6 +232,7 @@ impl OpenFileDialog {
                 });
             });
 
+        self.format_names = Some(format_names);
         if should_close {
             self.open = false;
             self.error = None;
@@ -101,3 +241,28 @@ impl OpenFileDialog {
         result
     }
 }
+
+/// Pick a built-in format, one of the user formats in `names`, or "Auto-detect" (empty).
+pub fn format_combo(ui: &mut egui::Ui, id: &str, format: &mut String, names: &[String]) {
+    let selected = if format.is_empty() {
+        "Auto-detect".to_string()
+    } else {
+        LineFormat::from_id(format).map_or_else(|| format.clone(), |builtin| builtin.label().to_string())
+    };
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(format, String::new(), "Auto-detect");
+            for builtin in LineFormat::BUILTIN {
+                ui.selectable_value(format, builtin.id().to_string(), builtin.label());
+            }
+            for name in names {
+                ui.selectable_value(format, name.clone(), name);
+            }
+        })
+        .response
+        .on_hover_text(format!(
+            "Auto-detect sniffs the first lines. User formats are read from {}",
+            custom::formats_path().display()
+        ));
+}
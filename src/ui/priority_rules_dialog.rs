@@ -0,0 +1,213 @@
+use eframe::egui;
+use crate::analyzer::LogEntry;
+use crate::analyzer::priority_rules::{self, PriorityRules, RuleDef};
+use crate::ui::log_viewer::{priority_color, priority_label};
+
+/// Editor for priority_rules.json, with a message tester and a preview of what each rule
+/// matches among the loaded entries.
+#[derive(Default)]
+pub struct PriorityRulesDialog {
+    pub open: bool,
+    rules: Vec<RuleDef>,
+    error: Option<String>,
+    test_service: String,
+    test_message: String,
+    /// Rules the preview was computed for, how many inferred entries each was the first to
+    /// match, and how many matched none
+    preview: Option<(Vec<RuleDef>, Vec<usize>, usize)>,
+    /// Rules last compiled and the result, so editing doesn't recompile every frame
+    compiled: Option<(Vec<RuleDef>, Result<PriorityRules, String>)>,
+}
+
+impl PriorityRulesDialog {
+    /// Load the rules in effect (priority_rules.json or the built-in ones).
+    pub fn load(&mut self) {
+        match priority_rules::load_defs() {
+            Ok(rules) => {
+                self.rules = rules;
+                self.error = None;
+            }
+            Err(e) => {
+                self.rules = priority_rules::default_defs();
+                self.error = Some(format!("{} - showing the built-in rules", e));
+            }
+        }
+        self.preview = None;
+        self.compiled = None;
+    }
+
+    /// Show the dialog. Returns the rules when the user clicks Save and they all compile.
+    pub fn show(&mut self, ctx: &egui::Context, entries: &[LogEntry]) -> Option<Vec<RuleDef>> {
+        if !self.open {
+            return None;
+        }
+
+        let mut result = None;
+        let mut should_close = false;
+        if self.compiled.as_ref().is_none_or(|(rules, _)| *rules != self.rules) {
+            self.compiled = Some((self.rules.clone(), PriorityRules::compile(&self.rules)));
+        }
+        let compiled = &self.compiled.as_ref().expect("compiled above").1;
+        // Counts are only shown while the rules are still the ones they were computed for
+        let preview = self.preview.as_ref()
+            .filter(|(rules, _, _)| *rules == self.rules)
+            .map(|(_, counts, unmatched)| (counts.clone(), *unmatched));
+
+        egui::Window::new("Priority Rules")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.label("Entries whose source gives no priority get the one of the first rule \
+                          matching their message. A rule with a service only applies to that service.");
+                ui.small(format!("Saved to {}", priority_rules::rules_path().display()));
+                ui.separator();
+
+                let mut move_up = None;
+                let mut remove = None;
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("priority_rules_grid")
+                        .num_columns(6)
+                        .striped(true)
+                        .spacing([8.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.strong("#");
+                            ui.strong("Regex");
+                            ui.strong("Service");
+                            ui.strong("Priority");
+                            ui.strong(if preview.is_some() { "Matches" } else { "" });
+                            ui.label("");
+                            ui.end_row();
+
+                            for (i, rule) in self.rules.iter_mut().enumerate() {
+                                ui.label(format!("{}", i + 1));
+                                ui.add(egui::TextEdit::singleline(&mut rule.regex)
+                                    .font(egui::TextStyle::Monospace)
+                                    .desired_width(280.0));
+                                ui.add(egui::TextEdit::singleline(&mut rule.service)
+                                    .desired_width(100.0)
+                                    .hint_text("any"));
+                                egui::ComboBox::from_id_salt(("rule_priority", i))
+                                    .selected_text(egui::RichText::new(priority_label(rule.priority))
+                                        .color(priority_color(rule.priority)))
+                                    .width(80.0)
+                                    .show_ui(ui, |ui| {
+                                        for priority in 0..=7 {
+                                            ui.selectable_value(&mut rule.priority, priority, priority_label(priority));
+                                        }
+                                    });
+                                match preview {
+                                    Some((ref counts, _)) => ui.label(format!("{}", counts[i])),
+                                    None => ui.label(""),
+                                };
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(i > 0, egui::Button::new("Up").small()).clicked() {
+                                        move_up = Some(i);
+                                    }
+                                    if ui.small_button("Remove").clicked() {
+                                        remove = Some(i);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+                if let Some(i) = move_up {
+                    self.rules.swap(i - 1, i);
+                }
+                if let Some(i) = remove {
+                    self.rules.remove(i);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add Rule").clicked() {
+                        self.rules.push(RuleDef { regex: String::new(), priority: 3, service: String::new() });
+                    }
+                    if ui.button("Restore Defaults").clicked() {
+                        self.rules = priority_rules::default_defs();
+                    }
+                    if ui.add_enabled(compiled.is_ok(), egui::Button::new("Preview on Loaded Entries")).clicked()
+                        && let Ok(rules) = compiled
+                    {
+                        let mut counts = vec![0; self.rules.len()];
+                        let mut unmatched = 0;
+                        for entry in entries.iter().filter(|e| e.priority_inferred) {
+                            match rules.matching_rule(&entry.service, &entry.message) {
+                                Some(i) => counts[i] += 1,
+                                None => unmatched += 1,
+                            }
+                        }
+                        self.preview = Some((self.rules.clone(), counts, unmatched));
+                    }
+                });
+                if let Some((ref counts, unmatched)) = preview {
+                    let total = counts.iter().sum::<usize>() + unmatched;
+                    ui.label(format!("{} loaded entries have an inferred priority; {} match no rule", total, unmatched));
+                }
+
+                ui.separator();
+                egui::Grid::new("priority_rules_test")
+                    .num_columns(2)
+                    .spacing([10.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Test service:");
+                        ui.add(egui::TextEdit::singleline(&mut self.test_service)
+                            .desired_width(150.0)
+                            .hint_text("e.g. myapp"));
+                        ui.end_row();
+
+                        ui.label("Test message:");
+                        ui.add(egui::TextEdit::singleline(&mut self.test_message)
+                            .desired_width(400.0)
+                            .hint_text("e.g. build finished, 0 errors"));
+                        ui.end_row();
+
+                        ui.label("Result:");
+                        match compiled {
+                            Ok(rules) => match rules.matching_rule(self.test_service.trim(), &self.test_message) {
+                                Some(i) => {
+                                    let priority = self.rules[i].priority;
+                                    ui.label(egui::RichText::new(format!("{} (rule {})", priority_label(priority), i + 1))
+                                        .color(priority_color(priority)));
+                                }
+                                None => {
+                                    ui.label("No rule matches - the source's default (INFO) is kept");
+                                }
+                            },
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::RED, e);
+                            }
+                        }
+                        ui.end_row();
+                    });
+
+                if let Some(ref err) = self.error {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        match compiled {
+                            Ok(_) => {
+                                result = Some(self.rules.clone());
+                                should_close = true;
+                            }
+                            Err(e) => self.error = Some(e.clone()),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+
+        if should_close {
+            self.open = false;
+            self.error = None;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,231 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::rc::Rc;
+use ruzstd::decoding::{BlockDecodingStrategy, FrameDecoder};
+use ruzstd::decoding::errors::{FrameDecoderError, ReadFrameHeaderError};
+
+const BUFFER_SIZE: usize = 128 * 1024;
+
+/// Compression formats logrotate commonly produces, detected from magic bytes rather than
+/// the file extension (`syslog.2.gz`, `messages-20261001.xz`, ...).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn detect(head: &[u8]) -> Self {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if head.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Number of raw (compressed) bytes read from the file so far, so progress still
+/// reflects how much of the file on disk has been consumed.
+#[derive(Clone, Default)]
+pub struct BytesRead(Rc<Cell<u64>>);
+
+impl BytesRead {
+    pub fn get(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+struct CountingReader<R> {
+    inner: R,
+    count: BytesRead,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.0.set(self.count.0.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// Open `path` for reading, decompressing on the fly if it is compressed.
+pub fn open(path: &str) -> io::Result<(Box<dyn BufRead>, BytesRead)> {
+    let file = File::open(path)?;
+    let count = BytesRead::default();
+    let mut raw = BufReader::with_capacity(BUFFER_SIZE, CountingReader { inner: file, count: count.clone() });
+
+    let decoder: Box<dyn Read> = match Compression::detect(raw.fill_buf()?) {
+        Compression::None => return Ok((Box::new(raw), count)),
+        // The multi-member decoders also handle files that were appended to after compression
+        Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(raw)),
+        Compression::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(raw)),
+        Compression::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(raw)),
+        Compression::Zstd => Box::new(ZstdDecoder::new(raw)?),
+    };
+    Ok((Box::new(BufReader::with_capacity(BUFFER_SIZE, decoder)), count))
+}
+
+/// Decodes every frame of a zstd file. ruzstd's `StreamingDecoder` stops after the first
+/// one, which would cut short files from pzstd (a frame per chunk) or appended to later.
+struct ZstdDecoder<R> {
+    source: R,
+    frame: FrameDecoder,
+}
+
+impl<R: BufRead> ZstdDecoder<R> {
+    fn new(source: R) -> io::Result<Self> {
+        let mut decoder = Self { source, frame: FrameDecoder::new() };
+        decoder.next_frame()?;
+        Ok(decoder)
+    }
+
+    /// Start decoding the next frame, passing over skippable ones. False at the end of
+    /// the input.
+    fn next_frame(&mut self) -> io::Result<bool> {
+        loop {
+            if self.source.fill_buf()?.is_empty() {
+                return Ok(false);
+            }
+            match self.frame.reset(&mut self.source) {
+                Ok(()) => return Ok(true),
+                Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame { length, .. })) => {
+                    io::copy(&mut (&mut self.source).take(length.into()), &mut io::sink())?;
+                }
+                Err(e) => return Err(zstd_error(e)),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for ZstdDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            // Blocks are decoded until enough can be collected to fill `buf`, or the frame ends
+            while self.frame.can_collect() < buf.len() && !self.frame.is_finished() {
+                let needed = buf.len() - self.frame.can_collect();
+                self.frame.decode_blocks(&mut self.source, BlockDecodingStrategy::UptoBytes(needed))
+                    .map_err(zstd_error)?;
+            }
+            let n = self.frame.read(buf)?;
+            if n > 0 || !self.next_frame()? {
+                return Ok(n);
+            }
+        }
+    }
+}
+
+fn zstd_error(e: FrameDecoderError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TEXT: &str = "Oct 11 22:14:15 host sshd[1]: Accepted publickey for bob\n\
+                        Oct 11 22:14:16 host cron[2]: (root) CMD (run-parts)\n";
+
+    /// Write `data` to a file of its own under the temp dir and read it back through `open`.
+    fn read_back(name: &str, data: &[u8]) -> (Vec<String>, u64) {
+        let path = std::env::temp_dir().join(format!("jlog-{}-{}", name, std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        let (reader, bytes_read) = open(path.to_str().unwrap()).unwrap();
+        let lines = reader.lines().collect::<io::Result<Vec<_>>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (lines, bytes_read.get())
+    }
+
+    /// The file read back as the two lines of `TEXT` repeated `times`, with the compressed
+    /// size counted as read.
+    fn assert_reads_back(name: &str, data: &[u8], times: usize) {
+        let (lines, bytes_read) = read_back(name, data);
+        assert_eq!(lines, TEXT.lines().cycle().take(2 * times).collect::<Vec<_>>());
+        assert_eq!(bytes_read, data.len() as u64);
+    }
+
+    fn gzip(text: &str) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn xz(text: &str) -> Vec<u8> {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn bzip2(text: &str) -> Vec<u8> {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zstd(text: &str) -> Vec<u8> {
+        ruzstd::encoding::compress_to_vec(text.as_bytes(), ruzstd::encoding::CompressionLevel::Fastest)
+    }
+
+    #[test]
+    fn formats_are_detected_from_magic_bytes() {
+        assert_eq!(Compression::detect(&gzip(TEXT)), Compression::Gzip);
+        assert_eq!(Compression::detect(&xz(TEXT)), Compression::Xz);
+        assert_eq!(Compression::detect(&bzip2(TEXT)), Compression::Bzip2);
+        assert_eq!(Compression::detect(&zstd(TEXT)), Compression::Zstd);
+        assert_eq!(Compression::detect(TEXT.as_bytes()), Compression::None);
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_reads_back("plain", TEXT.as_bytes(), 1);
+    }
+
+    #[test]
+    fn gzip_members() {
+        assert_reads_back("gzip", &gzip(TEXT), 1);
+        assert_reads_back("gzip-appended", &[gzip(TEXT), gzip(TEXT)].concat(), 2);
+    }
+
+    #[test]
+    fn xz_streams() {
+        assert_reads_back("xz", &xz(TEXT), 1);
+        assert_reads_back("xz-appended", &[xz(TEXT), xz(TEXT)].concat(), 2);
+    }
+
+    #[test]
+    fn bzip2_streams() {
+        assert_reads_back("bzip2", &bzip2(TEXT), 1);
+        assert_reads_back("bzip2-appended", &[bzip2(TEXT), bzip2(TEXT)].concat(), 2);
+    }
+
+    #[test]
+    fn zstd_frames() {
+        assert_reads_back("zstd", &zstd(TEXT), 1);
+        // A skippable frame between two data frames, as pzstd writes
+        let mut skippable = 0x184D2A50u32.to_le_bytes().to_vec();
+        skippable.extend_from_slice(&4u32.to_le_bytes());
+        skippable.extend_from_slice(&[1, 2, 3, 4]);
+        assert_reads_back("zstd-frames", &[zstd(TEXT), skippable, zstd(TEXT), zstd(TEXT)].concat(), 3);
+    }
+
+    #[test]
+    fn corrupt_zstd_header_is_an_error() {
+        let path = std::env::temp_dir().join(format!("jlog-zstd-corrupt-{}", std::process::id()));
+        std::fs::write(&path, [0x28, 0xb5, 0x2f, 0xfd, 0xff, 0xff]).unwrap();
+        assert!(open(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
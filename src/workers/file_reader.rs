@@ -1,155 +1,788 @@
 use std::io::BufRead;
-use crossbeam_channel::Sender;
-use regex::Regex;
-use once_cell::sync::Lazy;
-use serde::Deserialize;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 use crate::analyzer::LogEntry;
-use crate::background::BackgroundMessage;
+use crate::background::{BackgroundMessage, BackgroundCommand};
+use crate::formats::dmesg;
+use crate::formats::export::{self, ExportReader};
+use crate::formats::journal_file::{self, JournalFile};
 use crate::journalctl::JournalEntry;
+use crate::workers::decompress::{self, BytesRead};
+use crate::workers::line_parser::{self, LineFormat, LineParser, Parsed, UnparsedLines};
+use crate::workers::multiline::MultilineRules;
+use crate::workers::parse_options::ParseOptions;
 
-/// Matches the JSON format written by log_writer::save_logs()
-#[derive(Deserialize)]
-struct SavedJsonEntry {
-    #[serde(default)]
-    timestamp: String,
-    #[serde(default)]
-    priority: u8,
-    #[serde(default)]
-    service: String,
-    #[serde(default)]
-    message: String,
-}
-
-/// Matches the plaintext format written by log_writer::save_logs():
-/// "2026-02-11 10:30:45 sshd[6]: message"
-static SAVED_PLAINTEXT_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^(\d{4}-\d{2}-\d{2}\s+\d{2}:\d{2}:\d{2})\s+(\S+)\[(\d+)\]:\s*(.*)$").unwrap()
-});
-
-pub fn read_file(path: String, tx: Sender<BackgroundMessage>) {
+/// How often follow mode checks for appended data and rotation.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Read a single file. With `follow`, keep reading lines appended to it after the initial
+/// load until a command arrives on (or the app drops) the given channel.
+pub fn read_file(
+    path: String,
+    multiline: MultilineRules,
+    options: ParseOptions,
+    follow: Option<Receiver<BackgroundCommand>>,
+    tx: Sender<BackgroundMessage>,
+) {
+    std::thread::spawn(move || {
+        if let Err(e) = do_read(&path, &multiline, &options, follow.as_ref(), &tx) {
+            let _ = tx.send(BackgroundMessage::Error(format!("File read error: {}", e)));
+        }
+    });
+}
+
+/// Whether `path` is a text log that follow mode can keep reading. Binary journal files and
+/// export streams are read once.
+pub fn is_followable(path: &str) -> bool {
+    let Ok((mut reader, _)) = decompress::open(path) else {
+        return false;
+    };
+    reader.fill_buf().is_ok_and(|head| !journal_file::is_journal_file(head) && !export::is_export_stream(head))
+}
+
+/// Read several files, or every file in the given directories, as one log: entries are
+/// merged by timestamp, tagged with their source file, and lines repeated across files
+/// (where rotated files overlap) are dropped.
+pub fn read_files(paths: Vec<String>, multiline: MultilineRules, options: ParseOptions, tx: Sender<BackgroundMessage>) {
     std::thread::spawn(move || {
-        if let Err(e) = do_read(&path, &tx) {
+        if let Err(e) = do_read_files(&paths, &multiline, &options, &tx) {
             let _ = tx.send(BackgroundMessage::Error(format!("File read error: {}", e)));
         }
     });
 }
 
-fn do_read(path: &str, tx: &Sender<BackgroundMessage>) -> anyhow::Result<()> {
-    let file = std::fs::File::open(path)?;
-    let file_size = file.metadata()?.len() as f64;
-    let reader = std::io::BufReader::with_capacity(128 * 1024, file);
+/// Entries each file may read ahead of the merge before its reader waits.
+const MERGE_READ_AHEAD: usize = 1024;
+
+/// One file being merged: its reader's messages and the next entry to place.
+struct MergeSource<'a> {
+    label: &'a str,
+    source: Arc<str>,
+    size: f64,
+    rx: Receiver<BackgroundMessage>,
+    /// Next entry and its merge key: the entry's timestamp, or the last one seen in the
+    /// same file so entries without a time stay next to their neighbours
+    head: Option<(Option<i64>, LogEntry)>,
+    last_ts: Option<i64>,
+    /// The reader finished and every entry was taken
+    done: bool,
+    lines: usize,
+    percent: f32,
+}
+
+/// What the files have reported besides entries, combined for the app.
+#[derive(Default)]
+struct MergeStatus {
+    total_lines: usize,
+    errors: Vec<String>,
+    /// Format of the files so far, "mixed" once two differ
+    detected: Option<(String, Option<f32>)>,
+    unparsed: UnparsedLines,
+}
+
+impl MergeSource<'_> {
+    /// Read this file's messages until its next entry arrives or the reader finishes.
+    fn advance(&mut self, status: &mut MergeStatus, tx: &Sender<BackgroundMessage>) {
+        while self.head.is_none() && !self.done {
+            let Ok(msg) = self.rx.recv() else {
+                self.done = true;
+                break;
+            };
+            match msg {
+                BackgroundMessage::Entry(mut entry) => {
+                    entry.source = Some(self.source.clone());
+                    self.last_ts = entry.timestamp_us.or(self.last_ts);
+                    self.head = Some((self.last_ts, entry));
+                }
+                BackgroundMessage::Progress { lines, percent } => {
+                    self.lines = lines;
+                    self.percent = percent;
+                    return;
+                }
+                BackgroundMessage::Completed { total_lines: lines, .. } => status.total_lines += lines,
+                // A file that couldn't be read to the end; what was read is kept
+                BackgroundMessage::Error(e) => status.errors.push(format!("{}: {}", self.label, e)),
+                // Sent once per file, before it completes
+                BackgroundMessage::ParseFailures { count, samples } => {
+                    status.unparsed.add_file(self.label, count, samples);
+                    let _ = tx.send(status.unparsed.message());
+                }
+                BackgroundMessage::FormatDetected { format, confidence } => {
+                    let merged = match status.detected.take() {
+                        None => (format, confidence),
+                        Some((seen, seen_confidence)) if seen == format => {
+                            let lowest = match (seen_confidence, confidence) {
+                                (Some(a), Some(b)) => Some(a.min(b)),
+                                (a, b) => a.or(b),
+                            };
+                            (seen, lowest)
+                        }
+                        Some(_) => ("mixed".to_string(), None),
+                    };
+                    let _ = tx.send(BackgroundMessage::FormatDetected {
+                        format: merged.0.clone(),
+                        confidence: merged.1,
+                    });
+                    status.detected = Some(merged);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn do_read_files(
+    paths: &[String],
+    multiline: &MultilineRules,
+    options: &ParseOptions,
+    tx: &Sender<BackgroundMessage>,
+) -> anyhow::Result<()> {
+    let files = expand_paths(paths)?;
+    if files.is_empty() {
+        anyhow::bail!("no files found");
+    }
+    let labels = source_labels(&files);
+    let total_size = files.iter()
+        .map(|f| std::fs::metadata(f).map(|m| m.len() as f64).unwrap_or(0.0))
+        .sum::<f64>()
+        .max(1.0);
 
-    let mut lines_read = 0usize;
+    let mut status = MergeStatus::default();
     let mut entries_sent = 0usize;
-    let mut bytes_processed = 0u64;
-    let mut parse_errors = 0usize;
+    // Every file is read at once, a little ahead of the merge, which repeatedly takes the
+    // earliest of their next entries
+    let stopped = std::thread::scope(|scope| {
+        let mut sources = Vec::new();
+        let mut readers = Vec::new();
+        for (file, label) in files.iter().zip(&labels) {
+            let (file_tx, file_rx) = crossbeam_channel::bounded(MERGE_READ_AHEAD);
+            readers.push((label, scope.spawn(move || do_read(file, multiline, options, None, &file_tx))));
+            sources.push(MergeSource {
+                label,
+                source: Arc::from(label.as_str()),
+                size: std::fs::metadata(file).map(|m| m.len() as f64).unwrap_or(0.0),
+                rx: file_rx,
+                head: None,
+                last_ts: None,
+                done: false,
+                lines: 0,
+                percent: 0.0,
+            });
+        }
+
+        // Entries sharing the current timestamp, held until it changes so duplicates from
+        // overlapping files can be dropped
+        let mut same_time: Vec<LogEntry> = Vec::new();
+        let mut send = |entries: &mut Vec<LogEntry>| {
+            for entry in entries.drain(..) {
+                if tx.send(BackgroundMessage::Entry(entry)).is_err() {
+                    return false; // receiver dropped, stop
+                }
+                entries_sent += 1;
+            }
+            true
+        };
+        let stopped = loop {
+            let mut progressed = false;
+            for source in sources.iter_mut().filter(|s| s.head.is_none() && !s.done) {
+                let lines_before = source.lines;
+                source.advance(&mut status, tx);
+                progressed |= source.lines != lines_before;
+            }
+            if sources.iter().any(|s| s.head.is_none() && !s.done) {
+                // A reader reported progress instead of an entry; keep reading
+                if progressed {
+                    let done_size: f64 = sources.iter().map(|s| s.size * s.percent as f64 / 100.0).sum();
+                    let _ = tx.send(BackgroundMessage::Progress {
+                        lines: sources.iter().map(|s| s.lines).sum(),
+                        percent: (done_size / total_size * 100.0) as f32,
+                    });
+                }
+                continue;
+            }
+
+            // None sorts first, as entries with no time at all did in a sorted merge
+            let Some(next) = sources.iter_mut()
+                .filter(|s| s.head.is_some())
+                .min_by_key(|s| s.head.as_ref().map(|(key, _)| *key))
+            else {
+                break !send(&mut same_time);
+            };
+            let (_, entry) = next.head.take().unwrap();
+
+            if same_time.first().is_some_and(|held| held.timestamp_us != entry.timestamp_us || entry.timestamp_us.is_none())
+                && !send(&mut same_time)
+            {
+                break true;
+            }
+            if !same_time.iter().any(|held| is_overlap_duplicate(held, &entry)) {
+                same_time.push(entry);
+            }
+        };
+
+        // Readers still waiting to send see their channel close and stop
+        drop(sources);
+        for (label, reader) in readers {
+            let result = reader.join().unwrap_or_else(|_| Err(anyhow::anyhow!("reader thread panicked")));
+            if let Err(e) = result {
+                status.errors.push(format!("{}: File read error: {}", label, e));
+            }
+        }
+        stopped
+    });
+    if stopped {
+        return Ok(());
+    }
+
+    if !status.errors.is_empty() {
+        let _ = tx.send(BackgroundMessage::Error(status.errors.join("; ")));
+    }
+    let _ = tx.send(BackgroundMessage::Completed {
+        total_lines: status.total_lines,
+        entries: entries_sent,
+    });
+    Ok(())
+}
+
+/// The same line appearing in two different files, as where one rotated file ends and the
+/// next begins. Repeats within one file are real and kept.
+fn is_overlap_duplicate(a: &LogEntry, b: &LogEntry) -> bool {
+    a.source != b.source
+        && a.timestamp_us == b.timestamp_us
+        && a.priority == b.priority
+        && a.service == b.service
+        && a.message == b.message
+}
+
+/// Replace each directory with the regular, non-hidden files directly inside it.
+fn expand_paths(paths: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if !path.is_dir() {
+            files.push(path.to_string_lossy().into_owned());
+            continue;
+        }
+        let mut dir_files: Vec<String> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path().to_string_lossy().into_owned())
+            .collect();
+        dir_files.sort();
+        files.extend(dir_files);
+    }
+    Ok(files)
+}
+
+/// Short names for the source column: the file name, or the full path when two files
+/// share a name.
+fn source_labels(files: &[String]) -> Vec<String> {
+    let names: Vec<String> = files.iter()
+        .map(|f| Path::new(f).file_name().map_or_else(|| f.clone(), |n| n.to_string_lossy().into_owned()))
+        .collect();
+    let unique: HashSet<&String> = names.iter().collect();
+    if unique.len() == names.len() {
+        names
+    } else {
+        files.to_vec()
+    }
+}
+
+fn do_read(
+    path: &str,
+    multiline: &MultilineRules,
+    options: &ParseOptions,
+    follow: Option<&Receiver<BackgroundCommand>>,
+    tx: &Sender<BackgroundMessage>,
+) -> anyhow::Result<()> {
+    let metadata = std::fs::metadata(path)?;
+    let file_size = metadata.len() as f64;
+    let mut options = options.clone();
+    // The last write bounds the newest entry; a followed file keeps growing, so use now
+    if follow.is_none() {
+        options.clock.anchor_us = metadata.modified().ok()
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_micros());
+    }
+    let (mut reader, bytes_read) = decompress::open(path)?;
+
+    let head = reader.fill_buf()?;
+    if journal_file::is_journal_file(head) {
+        let _ = tx.send(BackgroundMessage::FormatDetected { format: "journal-file".to_string(), confidence: None });
+        return read_journal_file(reader, tx);
+    }
+    if export::is_export_stream(head) {
+        let _ = tx.send(BackgroundMessage::FormatDetected { format: "journal-export".to_string(), confidence: None });
+        return read_export(reader, file_size, &bytes_read, tx);
+    }
+
+    let format = detect_format(head, &options, tx);
+    // dmesg only counts seconds since boot; look for the kernel saying when that was
+    if format == Some(LineFormat::Dmesg) && options.clock.boot_us.is_none() {
+        options.clock.boot_us = decompress::open(path).ok().and_then(|(file, _)| dmesg::find_boot_time(file));
+    }
+    let parser = LineParser::new(options, format, Some(Path::new(path)));
+    let mut text = TextReader::new(multiline, parser, tx);
+    let percent = || if file_size > 0.0 {
+        (bytes_read.get() as f32 / file_size as f32) * 100.0
+    } else {
+        0.0
+    };
+    // Corrupt or truncated compressed data: keep everything read up to here
+    let read_error = match text.read_to_eof(&mut reader, follow.is_some(), &percent) {
+        Ok(false) => return Ok(()), // receiver dropped, stop
+        Ok(true) => None,
+        Err(e) => Some(e),
+    };
+    // When following, an audit event at the end may still get records
+    if !text.flush(follow.is_none() || read_error.is_some()) {
+        return Ok(());
+    }
+
+    text.report_unparsed();
+    // Reported before Completed, which keeps it in the status bar
+    if let Some(ref e) = read_error {
+        let _ = tx.send(BackgroundMessage::Error(format!("File read error: {}", e)));
+    }
+    let _ = tx.send(BackgroundMessage::Completed {
+        total_lines: text.lines_read,
+        entries: text.entries_sent,
+    });
+
+    match follow {
+        Some(cmd_rx) if read_error.is_none() => follow_file(path, reader, bytes_read, text, cmd_rx),
+        _ => Ok(()),
+    }
+}
+
+/// Use the forced format, or sniff one from the head, and tell the app which it is.
+pub(crate) fn detect_format(
+    head: &[u8],
+    options: &ParseOptions,
+    tx: &Sender<BackgroundMessage>,
+) -> Option<LineFormat> {
+    let (format, confidence) = match options.forced_format() {
+        Some(format) => (Some(format), None),
+        None => match line_parser::sniff(head, options) {
+            Some((format, confidence)) => (Some(format), Some(confidence)),
+            None => (None, Some(0.0)),
+        },
+    };
+    let _ = tx.send(BackgroundMessage::FormatDetected {
+        format: format.map_or_else(|| "unknown".to_string(), |f| options.format_name(f)),
+        confidence,
+    });
+    format
+}
+
+/// Keep reading lines appended to `path` after the initial load, like `tail -F`: when
+/// logrotate moves the file away (new inode) or truncates it, reopen and start from the top.
+/// Runs until the app sends a command or drops the command channel.
+fn follow_file(
+    path: &str,
+    mut reader: Box<dyn BufRead>,
+    mut bytes_read: BytesRead,
+    mut text: TextReader,
+    cmd_rx: &Receiver<BackgroundCommand>,
+) -> anyhow::Result<()> {
+    let mut identity = std::fs::metadata(path).ok().and_then(|m| file_identity(&m));
+
+    loop {
+        let lines_before = text.lines_read;
+        if !text.read_to_eof(&mut reader, true, &|| 0.0)? {
+            return Ok(());
+        }
+        if text.lines_read != lines_before {
+            text.report_unparsed();
+            let _ = text.tx.send(BackgroundMessage::Progress { lines: text.lines_read, percent: 0.0 });
+            continue;
+        }
+
+        // Nothing new: the held-back entry can't get more continuation lines now
+        if !text.flush(false) {
+            return Ok(());
+        }
+        match cmd_rx.recv_timeout(FOLLOW_POLL_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => {}
+            // Cancel/Disconnect, or the app dropped the channel
+            _ => {
+                text.flush(true);
+                return Ok(());
+            }
+        }
+
+        // Moved away and not recreated yet: keep waiting
+        let Ok(meta) = std::fs::metadata(path) else {
+            continue;
+        };
+        let rotated = file_identity(&meta) != identity;
+        let truncated = meta.len() < bytes_read.get();
+        // Lines written to the old file between the last poll and the rename are still
+        // readable through the old handle; its last line is complete now
+        if rotated && !text.read_to_eof(&mut reader, false, &|| 0.0)? {
+            return Ok(());
+        }
+        if (rotated || truncated)
+            && let Ok((new_reader, new_bytes_read)) = decompress::open(path)
+        {
+            reader = new_reader;
+            bytes_read = new_bytes_read;
+            identity = file_identity(&meta);
+            text.partial.clear();
+        }
+    }
+}
+
+/// Device and inode, which change when logrotate moves the file and creates a new one.
+#[cfg(unix)]
+fn file_identity(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// No stable file id on this platform; rotation is only noticed through truncation.
+#[cfg(not(unix))]
+fn file_identity(_meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Line-by-line parsing state for text logs, kept across reads so follow mode can resume
+/// where the last read stopped. Streams (SSH, stdin) are read through it one line at a time.
+pub(crate) struct TextReader<'a> {
+    multiline: &'a MultilineRules,
+    parser: LineParser,
+    tx: &'a Sender<BackgroundMessage>,
+    pub(crate) lines_read: usize,
+    pub(crate) entries_sent: usize,
+    /// Lines no format matched, sent on as raw entries
+    unparsed: UnparsedLines,
+    /// `unparsed.count` when it was last reported to the app
+    unparsed_reported: usize,
+    /// Last parsed entry, held back until we know no continuation lines follow it
+    pending: Option<LogEntry>,
+    /// Start of a line whose newline hasn't been written yet (follow mode)
+    partial: Vec<u8>,
+}
+
+/// Outcome of `TextReader::read_line`.
+pub(crate) enum LineRead {
+    Line,
+    /// End of the input, or of what has been written so far when waiting for a newline
+    Eof,
+    /// The app dropped the receiver
+    Stopped,
+}
+
+impl<'a> TextReader<'a> {
+    pub(crate) fn new(multiline: &'a MultilineRules, parser: LineParser, tx: &'a Sender<BackgroundMessage>) -> Self {
+        Self {
+            multiline,
+            parser,
+            tx,
+            lines_read: 0,
+            entries_sent: 0,
+            unparsed: UnparsedLines::default(),
+            unparsed_reported: 0,
+            pending: None,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Parse lines until EOF. With `wait_for_newline`, an unterminated last line is kept
+    /// for the next call instead of being parsed. Returns false if the receiver dropped.
+    fn read_to_eof(&mut self, reader: &mut dyn BufRead, wait_for_newline: bool, percent: &dyn Fn() -> f32) -> std::io::Result<bool> {
+        loop {
+            match self.read_line(reader, wait_for_newline)? {
+                LineRead::Line => {}
+                LineRead::Eof => return Ok(true),
+                LineRead::Stopped => return Ok(false),
+            }
+            if self.lines_read.is_multiple_of(50_000) {
+                let _ = self.tx.send(BackgroundMessage::Progress { lines: self.lines_read, percent: percent() });
+            }
+        }
+    }
+
+    /// Read and parse the next line. With `wait_for_newline`, an unterminated last line is
+    /// kept for the next call instead of being parsed.
+    pub(crate) fn read_line(&mut self, reader: &mut dyn BufRead, wait_for_newline: bool) -> std::io::Result<LineRead> {
+        let mut buf = std::mem::take(&mut self.partial);
+        let n = reader.read_until(b'\n', &mut buf)?;
+        if n == 0 && buf.is_empty() {
+            return Ok(LineRead::Eof);
+        }
+        if wait_for_newline && buf.last() != Some(&b'\n') {
+            self.partial = buf;
+            return Ok(LineRead::Eof);
+        }
+
+        // Invalid UTF-8 is replaced rather than skipped, so the line still shows up
+        let line = String::from_utf8_lossy(&buf);
+        let handled = self.handle_line(line.trim_end_matches('\n').trim_end_matches('\r'));
+        // The emptied buffer is reused for the next line
+        buf.clear();
+        self.partial = buf;
+        Ok(if handled { LineRead::Line } else { LineRead::Stopped })
+    }
+
+    fn handle_line(&mut self, line: &str) -> bool {
+        self.lines_read += 1;
+        let framed = self.parser.is_framed();
+
+        if !framed
+            && let Some(ref mut prev) = self.pending
+            && !line.trim().is_empty()
+            && self.multiline.continues(line) == Some(true)
+        {
+            append_continuation(prev, line);
+            return true;
+        }
 
-    for line_result in reader.lines() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(_) => continue,
+        let log_entry = match self.parser.parse(line, self.lines_read) {
+            Parsed::Entry(entry) => entry,
+            Parsed::Partial => return true,
+            Parsed::Unparsed if line.trim().is_empty() => return true,
+            Parsed::Unparsed => {
+                // Counted even when folded, so the status bar shows how much didn't parse
+                self.unparsed.record(self.lines_read, line);
+                // A run of unknown lines is kept line by line, not folded into the first
+                if let Some(ref mut prev) = self.pending
+                    && self.multiline.unmatched_lines
+                    && !prev.raw
+                {
+                    append_continuation(prev, line);
+                    return true;
+                }
+                return self.hold(LogEntry::raw(self.lines_read, line));
+            }
         };
 
-        bytes_processed += line.len() as u64 + 1;
-        lines_read += 1;
+        // Continuation rules apply to the unwrapped text, not the JSON / CRI framing
+        if framed
+            && let Some(ref mut prev) = self.pending
+            && !log_entry.message.trim().is_empty()
+            && self.multiline.continues(&log_entry.message) == Some(true)
+        {
+            append_continuation(prev, &log_entry.message);
+            return true;
+        }
+
+        self.hold(log_entry)
+    }
+
+    /// Hold back `entry` for continuation lines, sending the one held before it.
+    fn hold(&mut self, entry: LogEntry) -> bool {
+        match self.pending.replace(entry) {
+            Some(prev) => self.send(prev),
+            None => true,
+        }
+    }
+
+    /// Tell the app about lines that failed to parse since the last report.
+    pub(crate) fn report_unparsed(&mut self) {
+        if self.unparsed.count != self.unparsed_reported {
+            self.unparsed_reported = self.unparsed.count;
+            let _ = self.tx.send(self.unparsed.message());
+        }
+    }
+
+    /// Send the held-back entry, if any, and at the end of the input the ones the parser is
+    /// still assembling. An audit event is only complete when a record of another event or
+    /// its EOE arrives, so a pause in follow mode mustn't split it.
+    pub(crate) fn flush(&mut self, at_eof: bool) -> bool {
+        while at_eof && let Some(entry) = self.parser.finish() {
+            if !self.hold(entry) {
+                return false;
+            }
+        }
+        match self.pending.take() {
+            Some(entry) => self.send(entry),
+            None => true,
+        }
+    }
+
+    fn send(&mut self, entry: LogEntry) -> bool {
+        if self.tx.send(BackgroundMessage::Entry(entry)).is_err() {
+            return false;
+        }
+        self.entries_sent += 1;
+        true
+    }
+}
 
-        let log_entry = if let Some(entry) = parse_line(&line, &mut parse_errors) {
-            journal_to_log_entry(lines_read, &entry)
-        } else if let Some(entry) = parse_saved_line(&line, lines_read) {
-            entry
-        } else {
-            parse_errors += 1;
+/// Read a binary systemd `.journal` file. Progress is reported per entry rather than per byte,
+/// since entries are reached through the entry-array chain, not in file order.
+fn read_journal_file(mut reader: impl BufRead, tx: &Sender<BackgroundMessage>) -> anyhow::Result<()> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let journal = JournalFile::parse(data)?;
+    let offsets = journal.entry_offsets()?;
+    let total = offsets.len().max(1) as f32;
+
+    let mut entries_sent = 0usize;
+    for (i, offset) in offsets.iter().enumerate() {
+        // Files that were still being written can end in a half-written entry; skip it
+        let Ok(fields) = journal.read_entry(*offset) else {
             continue;
         };
-        if tx.send(BackgroundMessage::Entry(log_entry)).is_err() {
+        let entry = JournalEntry::from_raw_fields(fields).to_log_entry(i + 1);
+        if tx.send(BackgroundMessage::Entry(entry)).is_err() {
             return Ok(()); // receiver dropped, stop
         }
         entries_sent += 1;
 
-        if lines_read % 50_000 == 0 {
+        if (i + 1).is_multiple_of(50_000) {
+            let percent = (i + 1) as f32 / total * 100.0;
+            let _ = tx.send(BackgroundMessage::Progress { lines: i + 1, percent });
+        }
+    }
+
+    let _ = tx.send(BackgroundMessage::Completed {
+        total_lines: offsets.len(),
+        entries: entries_sent,
+    });
+
+    Ok(())
+}
+
+/// Read a `journalctl -o export` stream. Each record becomes one entry; line numbers count
+/// records.
+fn read_export(
+    reader: impl BufRead,
+    file_size: f64,
+    bytes_read: &BytesRead,
+    tx: &Sender<BackgroundMessage>,
+) -> anyhow::Result<()> {
+    let mut export = ExportReader::new(reader);
+    let mut records = 0usize;
+
+    while let Some(fields) = export.next_entry()? {
+        records += 1;
+        let entry = JournalEntry::from_raw_fields(fields).to_log_entry(records);
+        if tx.send(BackgroundMessage::Entry(entry)).is_err() {
+            return Ok(()); // receiver dropped, stop
+        }
+
+        if records.is_multiple_of(50_000) {
             let percent = if file_size > 0.0 {
-                (bytes_processed as f32 / file_size as f32) * 100.0
+                (bytes_read.get() as f32 / file_size as f32) * 100.0
             } else {
                 0.0
             };
-            let _ = tx.send(BackgroundMessage::Progress { lines: lines_read, percent });
+            let _ = tx.send(BackgroundMessage::Progress { lines: records, percent });
         }
     }
 
     let _ = tx.send(BackgroundMessage::Completed {
-        total_lines: lines_read,
-        entries: entries_sent,
+        total_lines: records,
+        entries: records,
     });
 
     Ok(())
 }
 
-fn parse_line(line: &str, _parse_errors: &mut usize) -> Option<JournalEntry> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
+/// Fold a continuation line into the previous entry's message, keeping its indentation.
+fn append_continuation(entry: &mut LogEntry, line: &str) {
+    entry.message.push('\n');
+    entry.message.push_str(line.trim_end());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str, ts: i64, message: &str) -> LogEntry {
+        let mut entry = LogEntry::raw(1, message);
+        entry.timestamp_us = Some(ts);
+        entry.service = "sshd".to_string();
+        entry.source = Some(Arc::from(source));
+        entry
     }
 
-    if let Some(entry) = JournalEntry::from_syslog_line(line) {
-        return Some(entry);
+    /// Empty directory under the temp dir, unique to the test.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("jlog-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
     }
 
-    if line.starts_with('{') {
-        if let Ok(entry) = serde_json::from_str::<JournalEntry>(line) {
-            return Some(entry);
-        }
+    #[test]
+    fn overlap_duplicates_need_another_file_and_the_same_line() {
+        let kept = entry("auth.log.1", 1_000, "Accepted publickey for bob");
+        assert!(is_overlap_duplicate(&kept, &entry("auth.log", 1_000, "Accepted publickey for bob")));
+        // A line logged twice in the same file is kept both times
+        assert!(!is_overlap_duplicate(&kept, &entry("auth.log.1", 1_000, "Accepted publickey for bob")));
+        assert!(!is_overlap_duplicate(&kept, &entry("auth.log", 1_001, "Accepted publickey for bob")));
+        assert!(!is_overlap_duplicate(&kept, &entry("auth.log", 1_000, "Accepted publickey for eve")));
+
+        let mut other_service = entry("auth.log", 1_000, "Accepted publickey for bob");
+        other_service.service = "cron".to_string();
+        assert!(!is_overlap_duplicate(&kept, &other_service));
+        let mut other_priority = entry("auth.log", 1_000, "Accepted publickey for bob");
+        other_priority.priority = 3;
+        assert!(!is_overlap_duplicate(&kept, &other_priority));
     }
 
-    None
-}
+    #[test]
+    fn labels_are_file_names_unless_two_match() {
+        let files = ["/var/log/syslog".to_string(), "/var/log/syslog.1".to_string()];
+        assert_eq!(source_labels(&files), ["syslog", "syslog.1"]);
 
-/// Parse lines in the format saved by log_writer (both JSON and plaintext).
-fn parse_saved_line(line: &str, line_num: usize) -> Option<LogEntry> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
+        let files = ["/srv/a/app.log".to_string(), "/srv/b/app.log".to_string(), "/srv/b/db.log".to_string()];
+        assert_eq!(source_labels(&files), files);
     }
 
-    // Saved JSON: {"line":1,"timestamp":"...","priority":6,"service":"sshd","message":"..."}
-    if line.starts_with('{') {
-        if let Ok(saved) = serde_json::from_str::<SavedJsonEntry>(line) {
-            return Some(LogEntry {
-                line_num,
-                timestamp: saved.timestamp,
-                priority: saved.priority,
-                service: saved.service,
-                message: saved.message,
-            });
+    #[test]
+    fn directories_expand_to_their_visible_files_in_order() {
+        let dir = temp_dir("expand");
+        for name in ["syslog.2", "syslog", ".hidden", "syslog.1"] {
+            std::fs::write(dir.join(name), "").unwrap();
         }
-    }
+        std::fs::create_dir(dir.join("old")).unwrap();
+        std::fs::write(dir.join("old").join("syslog.3"), "").unwrap();
 
-    // Saved plaintext: "2026-02-11 10:30:45 sshd[6]: message"
-    if let Some(caps) = SAVED_PLAINTEXT_REGEX.captures(line) {
-        return Some(LogEntry {
-            line_num,
-            timestamp: caps[1].to_string(),
-            service: caps[2].to_string(),
-            priority: caps[3].parse().unwrap_or(6),
-            message: caps[4].to_string(),
-        });
+        let single = dir.join("old").join("syslog.3").to_string_lossy().into_owned();
+        let files = expand_paths(&[single.clone(), dir.to_string_lossy().into_owned()]).unwrap();
+        let expected: Vec<String> = std::iter::once(single)
+            .chain(["syslog", "syslog.1", "syslog.2"].map(|name| dir.join(name).to_string_lossy().into_owned()))
+            .collect();
+        assert_eq!(files, expected);
+
+        // Paths that aren't directories are passed on, even missing ones
+        let missing = dir.join("missing.log").to_string_lossy().into_owned();
+        assert_eq!(expand_paths(std::slice::from_ref(&missing)).unwrap(), [missing]);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    None
-}
+    #[test]
+    fn files_merge_by_time_without_overlapping_lines() {
+        let dir = temp_dir("merge");
+        std::fs::write(dir.join("a.log"), "2024-01-01T00:00:01Z host app[1]: one\n\
+                                           2024-01-01T00:00:03Z host app[1]: three\n\
+                                           2024-01-01T00:00:04Z host app[1]: four\n").unwrap();
+        std::fs::write(dir.join("b.log"), "2024-01-01T00:00:02Z host app[1]: two\n\
+                                           2024-01-01T00:00:03Z host app[1]: three\n\
+                                           2024-01-01T00:00:05Z host app[1]: five\n").unwrap();
 
-fn journal_to_log_entry(line_num: usize, entry: &JournalEntry) -> LogEntry {
-    let timestamp = entry.timestamp_secs()
-        .and_then(|secs| {
-            chrono::DateTime::from_timestamp(secs, 0)
-                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-        })
-        .unwrap_or_default();
-
-    LogEntry {
-        line_num,
-        timestamp,
-        priority: entry.priority_num(),
-        service: entry.service(),
-        message: entry.msg().to_string(),
+        let (tx, rx) = crossbeam_channel::unbounded();
+        do_read_files(&[dir.to_string_lossy().into_owned()], &MultilineRules::default(), &ParseOptions::default(), &tx)
+            .unwrap();
+        drop(tx);
+        let mut merged = Vec::new();
+        let mut completed = None;
+        for msg in rx {
+            match msg {
+                BackgroundMessage::Entry(entry) => merged.push(format!("{} {}", entry.source.unwrap(), entry.message)),
+                BackgroundMessage::Completed { total_lines, entries } => completed = Some((total_lines, entries)),
+                _ => {}
+            }
+        }
+        assert_eq!(merged, ["a.log one", "b.log two", "a.log three", "a.log four", "b.log five"]);
+        assert_eq!(completed, Some((6, 5)));
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,520 @@
+//! Text line formats, and picking one for a file by sniffing its first lines.
+//!
+//! Trying every parser on every line is slow and ambiguous (a saved jlog plaintext line is
+//! also valid syslog), so readers choose one format up front from a sample of the head and
+//! only fall back to the others, in a fixed order, for lines it doesn't match.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use regex::Regex;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use crate::analyzer::{Fields, LogEntry, parse_timestamp_text};
+use crate::background::BackgroundMessage;
+use crate::formats::{access_log, audit, dmesg};
+use crate::formats::container::{self, ContainerFormat, ContainerInfo, ContainerReader, Pushed};
+use crate::journalctl::JournalEntry;
+use crate::workers::parse_options::ParseOptions;
+
+/// Lines of the head looked at when choosing a format.
+const SNIFF_LINES: usize = 100;
+
+/// How far past the newest entry of a live source a yearless stamp may land before it is
+/// taken to be from the year before.
+const LIVE_ANCHOR_LEAD_US: i64 = 183 * 86_400 * 1_000_000;
+
+/// Unparsed lines kept as samples for the status bar, and how much of each.
+const UNPARSED_SAMPLES: usize = 10;
+const UNPARSED_SAMPLE_CHARS: usize = 200;
+
+/// Matches the plaintext format written by log_writer::save_logs():
+/// "2026-02-11 10:30:45[.123456] sshd[6]: message"
+static SAVED_PLAINTEXT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d{4}-\d{2}-\d{2}\s+\d{2}:\d{2}:\d{2}(?:\.\d+)?)\s+(\S+)\[(\d+)\]:\s*(.*)$").unwrap()
+});
+
+/// Matches the JSON format written by log_writer::save_logs()
+#[derive(Deserialize)]
+struct SavedJsonEntry {
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    priority: u8,
+    #[serde(default)]
+    facility: Option<u8>,
+    #[serde(default)]
+    service: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    #[serde(default)]
+    raw: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineFormat {
+    /// BSD (RFC 3164) or RFC 5424 syslog
+    Syslog,
+    /// `journalctl -o json`
+    JournalJson,
+    /// Docker json-file driver
+    Docker,
+    /// containerd / CRI-O
+    Cri,
+    /// jlog's own JSON save format
+    SavedJson,
+    /// jlog's own plaintext save format
+    SavedText,
+    /// nginx / Apache common or combined access log
+    AccessLog,
+    /// Linux audit log (`/var/log/audit/audit.log`)
+    Audit,
+    /// Kernel ring buffer dump (`dmesg`, `dmesg -r`)
+    Dmesg,
+    /// Index into the user formats of the `ParseOptions`
+    Custom(usize),
+}
+
+impl LineFormat {
+    /// Built-in formats, in the order they are tried for lines the chosen format doesn't
+    /// match. Sniffing breaks ties the same way, so the strict formats come first, then
+    /// syslog, which matches the most; dmesg goes after it because `journalctl -o
+    /// short-monotonic` lines are valid dmesg too (`sniff` tells a real dmesg dump apart).
+    pub const BUILTIN: [LineFormat; 9] = [
+        LineFormat::Docker,
+        LineFormat::Cri,
+        LineFormat::JournalJson,
+        LineFormat::SavedJson,
+        LineFormat::SavedText,
+        LineFormat::AccessLog,
+        LineFormat::Audit,
+        LineFormat::Syslog,
+        LineFormat::Dmesg,
+    ];
+
+    /// Name used in settings and the status bar.
+    pub fn id(self) -> &'static str {
+        match self {
+            LineFormat::Syslog => "syslog",
+            LineFormat::JournalJson => "journal-json",
+            LineFormat::Docker => "docker",
+            LineFormat::Cri => "cri",
+            LineFormat::SavedJson => "jlog-json",
+            LineFormat::SavedText => "jlog-text",
+            LineFormat::AccessLog => "access-log",
+            LineFormat::Audit => "audit",
+            LineFormat::Dmesg => "dmesg",
+            LineFormat::Custom(_) => "custom",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LineFormat::Syslog => "Syslog (RFC 3164 / 5424)",
+            LineFormat::JournalJson => "journalctl -o json",
+            LineFormat::Docker => "Docker json-file",
+            LineFormat::Cri => "Kubernetes CRI",
+            LineFormat::SavedJson => "jlog saved JSON",
+            LineFormat::SavedText => "jlog saved text",
+            LineFormat::AccessLog => "nginx / Apache access log",
+            LineFormat::Audit => "Linux audit log",
+            LineFormat::Dmesg => "Kernel dmesg",
+            LineFormat::Custom(_) => "User format",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::BUILTIN.into_iter().find(|f| f.id() == id)
+    }
+
+    /// Whether `line` (trimmed, non-empty) is in this format.
+    fn matches(self, line: &str, options: &ParseOptions) -> bool {
+        match self {
+            LineFormat::Docker => ContainerFormat::detect(line) == Some(ContainerFormat::Docker),
+            LineFormat::Cri => ContainerFormat::detect(line) == Some(ContainerFormat::Cri),
+            _ => parse_as(self, line, options, 0).is_some(),
+        }
+    }
+}
+
+/// Choose a format from the first lines of `head`, with the share of sampled lines it
+/// matched (0.0-1.0). None if no format matches any of them.
+pub fn sniff(head: &[u8], options: &ParseOptions) -> Option<(LineFormat, f32)> {
+    let text = String::from_utf8_lossy(head);
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    // The buffer usually ends part-way through a line
+    if lines.len() > 1 {
+        lines.pop();
+    }
+    let sample: Vec<&str> = lines.iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    if sample.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(LineFormat, usize)> = None;
+    let mut dmesg_matched = 0;
+    for format in options.candidate_formats() {
+        let matched = sample.iter().filter(|line| format.matches(line, options)).count();
+        if format == LineFormat::Dmesg {
+            dmesg_matched = matched;
+        }
+        if matched > best.map_or(0, |(_, n)| n) {
+            best = Some((format, matched));
+        }
+    }
+    // Syslog wins ties with dmesg for `journalctl -o short-monotonic`, but a dmesg dump
+    // parses as syslog too, taking the word after the timestamp for a host name
+    if let Some((LineFormat::Syslog, matched)) = best
+        && matched == dmesg_matched
+        && !same_word_after_timestamp(&sample)
+    {
+        best = Some((LineFormat::Dmesg, matched));
+    }
+    best.map(|(format, matched)| (format, matched as f32 / sample.len() as f32))
+}
+
+/// Whether every `[<seconds>] ` line has the same first word, as the host name of
+/// short-monotonic output does. In a dmesg dump it is the subsystem or driver, which varies.
+fn same_word_after_timestamp(sample: &[&str]) -> bool {
+    let mut words = sample.iter()
+        .filter(|line| line.starts_with(['[', '<']))
+        .filter_map(|line| line.split_once("] ")?.1.split_whitespace().next());
+    match words.next() {
+        Some(first) => words.all(|word| word == first),
+        None => true,
+    }
+}
+
+/// Parse `line` as exactly `format`. Container and audit lines are parsed on their own,
+/// without joining partial chunks or grouping records; `LineParser` does that for the
+/// chosen format.
+fn parse_as(format: LineFormat, line: &str, options: &ParseOptions, line_num: usize) -> Option<LogEntry> {
+    match format {
+        LineFormat::Syslog => JournalEntry::from_syslog_line(line, &options.clock).map(|e| e.to_log_entry(line_num)),
+        LineFormat::JournalJson => parse_journal_json(line).map(|e| e.to_log_entry(line_num)),
+        LineFormat::Docker | LineFormat::Cri => {
+            let container = if format == LineFormat::Docker { ContainerFormat::Docker } else { ContainerFormat::Cri };
+            match ContainerReader::new(container, ContainerInfo::default()).push(line) {
+                Pushed::Entry(entry) => Some(entry.to_log_entry(line_num)),
+                _ => None,
+            }
+        }
+        LineFormat::SavedJson => parse_saved_json(line, line_num),
+        LineFormat::SavedText => parse_saved_text(line, line_num),
+        LineFormat::AccessLog => access_log::parse_access_line(line).map(|e| e.to_log_entry(line_num)),
+        LineFormat::Audit => audit::parse_audit_line(line).map(|e| e.to_log_entry(line_num)),
+        LineFormat::Dmesg => dmesg::parse_dmesg_line(line, options.clock.boot_us).map(|e| e.to_log_entry(line_num)),
+        LineFormat::Custom(i) => options.parse_custom_with(i, line).map(|e| e.to_log_entry(line_num)),
+    }
+}
+
+fn parse_journal_json(line: &str) -> Option<JournalEntry> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let entry = serde_json::from_str::<JournalEntry>(line).ok()?;
+    // Any JSON object deserializes; only accept real journal records
+    (!entry.message.is_null() || entry.realtime_timestamp.is_some()).then_some(entry)
+}
+
+/// Saved JSON: {"line":1,"timestamp":"...","priority":6,"service":"sshd","message":"..."}
+fn parse_saved_json(line: &str, line_num: usize) -> Option<LogEntry> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let saved = serde_json::from_str::<SavedJsonEntry>(line).ok()?;
+    // Every field is optional, so any object would do; require the ones save_logs writes
+    if saved.timestamp.is_empty() && saved.message.is_empty() {
+        return None;
+    }
+    Some(LogEntry {
+        line_num,
+        timestamp_us: parse_timestamp_text(&saved.timestamp),
+        priority: saved.priority,
+        priority_inferred: false,
+        facility: saved.facility,
+        service: saved.service,
+        message: saved.message,
+        raw_message: None,
+        fields: saved.fields.iter().collect(),
+        source: None,
+        raw: saved.raw,
+    })
+}
+
+/// Saved plaintext: "2026-02-11 10:30:45 sshd[6]: message"
+fn parse_saved_text(line: &str, line_num: usize) -> Option<LogEntry> {
+    let caps = SAVED_PLAINTEXT_REGEX.captures(line)?;
+    Some(LogEntry {
+        line_num,
+        timestamp_us: parse_timestamp_text(&caps[1]),
+        service: caps[2].to_string(),
+        priority: caps[3].parse().unwrap_or(6),
+        priority_inferred: false,
+        facility: None,
+        message: caps[4].to_string(),
+        raw_message: None,
+        fields: Fields::new(),
+        source: None,
+        raw: false,
+    })
+}
+
+/// Result of feeding one line to a `LineParser`.
+pub enum Parsed {
+    Entry(LogEntry),
+    /// A partial container chunk or audit record, held until the rest of its entry arrives.
+    Partial,
+    /// Empty, or no format matched.
+    Unparsed,
+}
+
+/// Lines of a source that matched no format, whether kept as raw entries or folded into the
+/// previous entry by the "lines that don't match" multi-line rule.
+#[derive(Default)]
+pub struct UnparsedLines {
+    pub count: usize,
+    /// The first few, as "<line number>: <text>"
+    pub samples: Vec<String>,
+}
+
+impl UnparsedLines {
+    pub fn record(&mut self, line_num: usize, line: &str) {
+        self.count += 1;
+        if self.samples.len() < UNPARSED_SAMPLES {
+            let text: String = line.trim().chars().take(UNPARSED_SAMPLE_CHARS).collect();
+            self.samples.push(format!("{}: {}", line_num, text));
+        }
+    }
+
+    /// Add the unparsed lines of one of several merged files.
+    pub fn add_file(&mut self, label: &str, count: usize, samples: Vec<String>) {
+        self.count += count;
+        let room = UNPARSED_SAMPLES.saturating_sub(self.samples.len());
+        self.samples.extend(samples.into_iter().take(room).map(|sample| format!("{}:{}", label, sample)));
+    }
+
+    pub fn message(&self) -> BackgroundMessage {
+        BackgroundMessage::ParseFailures { count: self.count, samples: self.samples.clone() }
+    }
+}
+
+/// Parses a source's lines with its chosen format, falling back to the others per line.
+pub struct LineParser {
+    options: ParseOptions,
+    /// The format forced in the settings or sniffed from the head; None tries them all
+    format: Option<LineFormat>,
+    /// Set when the chosen format is Docker / CRI, to join partial chunks
+    container: Option<ContainerReader>,
+    /// Set when the chosen format is audit, to group each event's records
+    audit: Option<audit::AuditReader>,
+    /// Whether the syslog clock's anchor follows the newest entry, for sources with no
+    /// mtime to bound them (stdin, SSH, followed files)
+    live: bool,
+}
+
+impl LineParser {
+    /// `path` names the container for Docker / CRI logs.
+    pub fn new(options: ParseOptions, format: Option<LineFormat>, path: Option<&Path>) -> Self {
+        let container_info = || path.map(ContainerInfo::from_path).unwrap_or_default();
+        let container = match format {
+            Some(LineFormat::Docker) => Some(ContainerReader::new(ContainerFormat::Docker, container_info())),
+            Some(LineFormat::Cri) => Some(ContainerReader::new(ContainerFormat::Cri, container_info())),
+            _ => None,
+        };
+        let audit = (format == Some(LineFormat::Audit)).then(audit::AuditReader::default);
+        let live = options.clock.anchor_us.is_none();
+        Self { options, format, container, audit, live }
+    }
+
+    /// Whether lines are wrapped in container framing, so continuation rules have to look
+    /// at the parsed message rather than the raw line.
+    pub fn is_framed(&self) -> bool {
+        self.container.is_some()
+    }
+
+    pub fn parse(&mut self, line: &str, line_num: usize) -> Parsed {
+        match self.parse_line(line, line_num) {
+            Parsed::Entry(mut entry) => {
+                self.infer_priority(&mut entry);
+                self.follow_newest(&entry);
+                Parsed::Entry(entry)
+            }
+            other => other,
+        }
+    }
+
+    fn parse_line(&mut self, line: &str, line_num: usize) -> Parsed {
+        let line = line.trim();
+        if line.is_empty() {
+            return Parsed::Unparsed;
+        }
+
+        if let Some(ref mut container) = self.container {
+            match container.push(line) {
+                Pushed::Entry(entry) => return Parsed::Entry(entry.to_log_entry(line_num)),
+                Pushed::Partial => return Parsed::Partial,
+                Pushed::Unparsed => {}
+            }
+        } else if let Some(ref mut audit) = self.audit
+            && let Some(record) = audit::parse_record(line)
+        {
+            return match audit.push(record, line_num) {
+                Some((entry, first_line)) => Parsed::Entry(entry.to_log_entry(first_line)),
+                None => Parsed::Partial,
+            };
+        } else if let Some(format) = self.format
+            && let Some(entry) = parse_as(format, line, &self.options, line_num)
+        {
+            return Parsed::Entry(entry);
+        }
+
+        // A format the user picked is used exclusively
+        if self.options.forced_format().is_some() {
+            return Parsed::Unparsed;
+        }
+        let chosen = self.format;
+        self.options.candidate_formats()
+            .filter(|format| Some(*format) != chosen)
+            .find_map(|format| parse_as(format, line, &self.options, line_num))
+            .map_or(Parsed::Unparsed, Parsed::Entry)
+    }
+
+    /// An entry still being assembled at the end of the input, if any. Called until it
+    /// returns None.
+    pub fn finish(&mut self) -> Option<LogEntry> {
+        let (entry, first_line) = self.audit.as_mut()?.finish()?;
+        let mut entry = entry.to_log_entry(first_line);
+        self.infer_priority(&mut entry);
+        self.follow_newest(&entry);
+        Some(entry)
+    }
+
+    /// Move a live source's anchor along with its newest entry. Until the first timestamp
+    /// the clock anchors on now; after it, yearless stamps are placed near the entries
+    /// before them, so a piped-in log from an earlier year keeps its order across New Year.
+    fn follow_newest(&mut self, entry: &LogEntry) {
+        if self.live && let Some(ts) = entry.timestamp_us {
+            let anchor = ts.saturating_add(LIVE_ANCHOR_LEAD_US);
+            let clock = &mut self.options.clock;
+            clock.anchor_us = Some(clock.anchor_us.map_or(anchor, |current| current.max(anchor)));
+        }
+    }
+
+    /// Apply the priority rules. A rule may raise container stderr above WARN but not lower
+    /// it, so "0 errors" on stderr doesn't end up as INFO.
+    fn infer_priority(&self, entry: &mut LogEntry) {
+        if !entry.priority_inferred {
+            return;
+        }
+        let stderr = entry.fields.get_all(container::STREAM_FIELD).any(|stream| stream == "stderr");
+        self.options.priority_rules().apply(entry);
+        if stderr {
+            entry.priority = entry.priority.min(container::STDERR_PRIORITY);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sniff_text(text: &str) -> Option<(LineFormat, f32)> {
+        sniff(text.as_bytes(), &ParseOptions::default())
+    }
+
+    #[test]
+    fn dmesg_dump_sniffs_as_dmesg() {
+        let text = "[    0.000000] Linux version 6.1.0 (gcc 12.2.0)\n\
+                    [    1.234567] usb 1-1: new high-speed USB device number 2\n\
+                    [    2.000001] EXT4-fs (sda1): mounted filesystem\n";
+        assert_eq!(sniff_text(text), Some((LineFormat::Dmesg, 1.0)));
+    }
+
+    #[test]
+    fn short_monotonic_sniffs_as_syslog() {
+        let text = "[    1.234567] myhost kernel: usb 1-1: new high-speed USB device number 2\n\
+                    [    5.000000] myhost systemd[1]: Started Journal Service.\n";
+        assert_eq!(sniff_text(text), Some((LineFormat::Syslog, 1.0)));
+    }
+
+    #[test]
+    fn saved_text_is_not_taken_for_syslog() {
+        let text = "2026-02-11 10:30:45 sshd[6]: Accepted publickey for bob\n\
+                    2026-02-11 10:30:46.123456 cron[3]: (root) CMD (run-parts)\n";
+        assert_eq!(sniff_text(text), Some((LineFormat::SavedText, 1.0)));
+    }
+
+    #[test]
+    fn confidence_is_the_share_of_matching_lines() {
+        let text = "Oct 11 22:14:15 host sshd[1]: one\n\
+                    Oct 11 22:14:16 host sshd[1]: two\n\
+                    Oct 11 22:14:17 host sshd[1]: three\n\
+                    \n\
+                    not a log line at all\n\
+                    partial line without a newl";
+        assert_eq!(sniff_text(text), Some((LineFormat::Syslog, 0.75)));
+        assert_eq!(sniff_text("just some text\n"), None);
+    }
+
+    #[test]
+    fn other_formats_are_tried_when_detected() {
+        let mut parser = LineParser::new(ParseOptions::default(), Some(LineFormat::Syslog), None);
+        let Parsed::Entry(entry) = parser.parse(r#"{"MESSAGE":"from json","__REALTIME_TIMESTAMP":"1700000000000000"}"#, 1) else {
+            panic!("journal JSON line not parsed");
+        };
+        assert_eq!(entry.message, "from json");
+    }
+
+    #[test]
+    fn forced_format_is_exclusive() {
+        let mut parser = LineParser::new(ParseOptions::forcing(LineFormat::Syslog), Some(LineFormat::Syslog), None);
+        let json = r#"{"MESSAGE":"from json","__REALTIME_TIMESTAMP":"1700000000000000"}"#;
+        assert!(matches!(parser.parse(json, 1), Parsed::Unparsed));
+        assert!(matches!(parser.parse("Oct 11 22:14:15 host sshd[1]: hello", 2), Parsed::Entry(_)));
+    }
+
+    #[test]
+    fn live_sources_place_yearless_stamps_near_the_newest_entry() {
+        // A log from an earlier year piped in: its first stamp is dated, the rest aren't
+        let mut parser = LineParser::new(ParseOptions::default(), None, None);
+        let mut timestamp = |line: &str| match parser.parse(line, 1) {
+            Parsed::Entry(entry) => entry.timestamp_us,
+            _ => panic!("not parsed: {}", line),
+        };
+        let dated = timestamp("2020-12-31T23:00:00Z host app[1]: dated");
+        let december = timestamp("Dec 31 23:59:00 host app[1]: yearless");
+        let january = timestamp("Jan  1 00:01:00 host app[1]: new year");
+        assert_eq!(dated, Some(1_609_455_600_000_000));
+        assert_eq!(december, Some(1_609_459_140_000_000));
+        assert_eq!(january, Some(1_609_459_260_000_000));
+    }
+
+    #[test]
+    fn inferred_priorities_keep_stderr_at_warn_or_above() {
+        let mut parser = LineParser::new(ParseOptions::forcing(LineFormat::Cri), Some(LineFormat::Cri), None);
+        let mut priority = |line: &str| match parser.parse(line, 1) {
+            Parsed::Entry(entry) => (entry.priority, entry.priority_inferred),
+            _ => panic!("not parsed: {}", line),
+        };
+        // "0 errors" is INFO by the rules, but stderr doesn't drop below WARN
+        assert_eq!(priority("2026-10-16T10:00:00Z stdout F build done, 0 errors"), (6, true));
+        assert_eq!(priority("2026-10-16T10:00:00Z stderr F build done, 0 errors"), (4, true));
+        assert_eq!(priority("2026-10-16T10:00:00Z stderr F panic: out of memory"), (2, true));
+        assert_eq!(priority("2026-10-16T10:00:00Z stdout F request failed"), (3, true));
+    }
+
+    #[test]
+    fn given_priorities_are_not_inferred() {
+        let mut parser = LineParser::new(ParseOptions::default(), None, None);
+        let Parsed::Entry(entry) = parser.parse("<14>Oct 11 22:14:15 host app[1]: fatal error", 1) else {
+            panic!("not parsed");
+        };
+        assert_eq!((entry.priority, entry.priority_inferred), (6, false));
+    }
+}
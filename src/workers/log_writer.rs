@@ -20,23 +20,36 @@ pub fn save_logs(
     match settings.format {
         SaveFormat::Json => {
             for entry in entries {
-                let obj = serde_json::json!({
+                let fields: serde_json::Map<String, serde_json::Value> = entry.fields.iter()
+                    .map(|(k, v)| (k.to_string(), serde_json::Value::from(v)))
+                    .collect();
+                let mut obj = serde_json::json!({
                     "line": entry.line_num,
-                    "timestamp": entry.timestamp,
+                    "timestamp": entry.timestamp_text(),
                     "priority": entry.priority,
+                    "facility": entry.facility,
                     "service": entry.service,
                     "message": entry.message,
+                    "fields": fields,
                 });
+                if entry.raw {
+                    obj["raw"] = serde_json::Value::Bool(true);
+                }
                 serde_json::to_writer(&mut file, &obj)?;
                 writeln!(file)?;
             }
         }
         SaveFormat::PlainText => {
             for entry in entries {
+                // Lines no format matched are written back as they were read
+                if entry.raw {
+                    writeln!(file, "{}", entry.message)?;
+                    continue;
+                }
                 writeln!(
                     file,
                     "{} {}[{}]: {}",
-                    entry.timestamp, entry.service, entry.priority, entry.message
+                    entry.timestamp_text(), entry.service, entry.priority, entry.message
                 )?;
             }
         }
@@ -1,3 +1,9 @@
+pub mod decompress;
 pub mod file_reader;
+pub mod line_parser;
 pub mod log_writer;
+pub mod multiline;
+pub mod parse_options;
 pub mod ssh_reader;
+pub mod stdin_reader;
+pub mod stream_reader;
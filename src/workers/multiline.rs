@@ -0,0 +1,39 @@
+use regex::Regex;
+
+/// Rules for folding continuation lines (stack traces, wrapped messages) into the
+/// previous entry instead of treating each physical line as its own entry.
+#[derive(Clone, Debug)]
+pub struct MultilineRules {
+    /// Lines starting with a space or tab continue the previous entry.
+    pub leading_whitespace: bool,
+    /// Lines that don't parse as a log entry continue the previous entry. Off by default:
+    /// they are kept as raw entries, so a file only partly understood doesn't look complete.
+    pub unmatched_lines: bool,
+    /// When set, only lines matching this pattern start a new entry; every other line is
+    /// a continuation. Overrides the two rules above.
+    pub start_pattern: Option<Regex>,
+}
+
+impl Default for MultilineRules {
+    fn default() -> Self {
+        Self {
+            leading_whitespace: true,
+            unmatched_lines: false,
+            start_pattern: None,
+        }
+    }
+}
+
+impl MultilineRules {
+    /// Decide from the raw text alone whether `line` continues the previous entry.
+    /// Returns None when the answer depends on whether the line parses.
+    pub fn continues(&self, line: &str) -> Option<bool> {
+        if let Some(ref re) = self.start_pattern {
+            return Some(!re.is_match(line));
+        }
+        if self.leading_whitespace && line.starts_with([' ', '\t']) {
+            return Some(true);
+        }
+        None
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use crate::analyzer::priority_rules::{self, PriorityRules};
+use crate::formats::custom::{self, CustomFormat};
+use crate::journalctl::{JournalEntry, SyslogClock};
+use crate::workers::line_parser::LineFormat;
+
+/// How a source's text lines are parsed, as set in the Open File dialog or SSH profile.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// Year / zone for syslog stamps that carry neither.
+    pub clock: SyslogClock,
+    /// User formats from formats.json, tried after the built-in ones.
+    custom_formats: Arc<Vec<CustomFormat>>,
+    /// Format every line must be parsed with, instead of sniffing one.
+    forced_format: Option<LineFormat>,
+    /// Rules from priority_rules.json for entries that carry no priority.
+    priority_rules: Arc<PriorityRules>,
+    /// Why priority_rules.json couldn't be used, in which case the built-in rules are.
+    rules_error: Option<String>,
+}
+
+impl ParseOptions {
+    /// Load the user formats and priority rules. `format` is the id of a built-in format
+    /// or the name of a user one to force, or empty to detect. A broken
+    /// priority_rules.json isn't an error: the built-in rules are used and `rules_error()`
+    /// says why.
+    pub fn new(clock: SyslogClock, format: &str) -> Result<Self, String> {
+        let custom_formats = custom::load_formats()?;
+        let forced_format = match format.trim() {
+            "" => None,
+            name => Some(
+                LineFormat::from_id(name)
+                    .or_else(|| custom_formats.iter().position(|f| f.name == name).map(LineFormat::Custom))
+                    .ok_or_else(|| format!("Unknown log format: {}", name))?,
+            ),
+        };
+        let (priority_rules, rules_error) = match priority_rules::load_rules() {
+            Ok(rules) => (rules, None),
+            Err(e) => (PriorityRules::default(), Some(e)),
+        };
+        Ok(Self {
+            clock,
+            custom_formats: Arc::new(custom_formats),
+            forced_format,
+            priority_rules: Arc::new(priority_rules),
+            rules_error,
+        })
+    }
+
+    /// Default options forcing `format`, without reading the user's config.
+    #[cfg(test)]
+    pub fn forcing(format: LineFormat) -> Self {
+        Self { forced_format: Some(format), ..Self::default() }
+    }
+
+    /// The format chosen for this source, if any.
+    pub fn forced_format(&self) -> Option<LineFormat> {
+        self.forced_format
+    }
+
+    /// Every format a line may be in: the built-ins, then the user formats in file order.
+    pub fn candidate_formats(&self) -> impl Iterator<Item = LineFormat> + use<> {
+        LineFormat::BUILTIN.into_iter().chain((0..self.custom_formats.len()).map(LineFormat::Custom))
+    }
+
+    /// Id of `format` for the status bar and settings (a user format's name).
+    pub fn format_name(&self, format: LineFormat) -> String {
+        match format {
+            LineFormat::Custom(i) => self.custom_formats[i].name.clone(),
+            builtin => builtin.id().to_string(),
+        }
+    }
+
+    pub fn priority_rules(&self) -> &PriorityRules {
+        &self.priority_rules
+    }
+
+    pub fn rules_error(&self) -> Option<&str> {
+        self.rules_error.as_deref()
+    }
+
+    /// Parse with the user format at `index`.
+    pub fn parse_custom_with(&self, index: usize, line: &str) -> Option<JournalEntry> {
+        self.custom_formats[index].parse(line, &self.clock)
+    }
+}
@@ -0,0 +1,21 @@
+use crossbeam_channel::{Sender, Receiver};
+use crate::background::{BackgroundMessage, BackgroundCommand};
+use crate::workers::multiline::MultilineRules;
+use crate::workers::parse_options::ParseOptions;
+use crate::workers::stream_reader;
+
+/// Stream logs piped into `jlog -`, e.g. `journalctl -f -o json | jlog -`.
+pub fn start_stdin(
+    multiline: MultilineRules,
+    options: ParseOptions,
+    tx: Sender<BackgroundMessage>,
+    cmd_rx: Receiver<BackgroundCommand>,
+) {
+    std::thread::spawn(move || {
+        let stdin = std::io::BufReader::new(std::io::stdin());
+        if let Err(e) = stream_reader::read_stream(stdin, &multiline, &options, &tx, &cmd_rx) {
+            let _ = tx.send(BackgroundMessage::Error(format!("stdin error: {}", e)));
+        }
+        let _ = tx.send(BackgroundMessage::StdinClosed);
+    });
+}
@@ -0,0 +1,160 @@
+use std::io::{BufRead, BufReader, Read};
+use crossbeam_channel::{Sender, Receiver};
+use crate::background::{BackgroundMessage, BackgroundCommand};
+use crate::formats::dmesg;
+use crate::formats::export::{self, ExportReader};
+use crate::journalctl::JournalEntry;
+use crate::workers::file_reader::{self, LineRead, TextReader};
+use crate::workers::line_parser::{LineFormat, LineParser};
+use crate::workers::multiline::MultilineRules;
+use crate::workers::parse_options::ParseOptions;
+
+/// Read text lines or export records from a live stream (SSH channel, stdin) until EOF or a
+/// Cancel/Disconnect command.
+pub fn read_stream<R: Read>(
+    mut reader: BufReader<R>,
+    multiline: &MultilineRules,
+    options: &ParseOptions,
+    tx: &Sender<BackgroundMessage>,
+    cmd_rx: &Receiver<BackgroundCommand>,
+) -> anyhow::Result<()> {
+    let head = reader.fill_buf()?;
+    if export::is_export_stream(head) {
+        let _ = tx.send(BackgroundMessage::FormatDetected { format: "journal-export".to_string(), confidence: None });
+        return read_export(reader, tx, cmd_rx);
+    }
+    // Only what has arrived so far is sniffed, which may be just the first line
+    let format = file_reader::detect_format(head, options, tx);
+    let mut options = options.clone();
+    if format == Some(LineFormat::Dmesg) && options.clock.boot_us.is_none() {
+        options.clock.boot_us = dmesg::find_boot_time(head);
+    }
+    let mut text = TextReader::new(multiline, LineParser::new(options, format, None), tx);
+
+    loop {
+        // Check for cancel/disconnect commands (non-blocking)
+        if let Ok(cmd) = cmd_rx.try_recv() {
+            match cmd {
+                BackgroundCommand::Cancel | BackgroundCommand::Disconnect => {
+                    return Ok(());
+                }
+            }
+        }
+
+        match text.read_line(&mut reader, false) {
+            Ok(LineRead::Line) => {}
+            Ok(LineRead::Eof) => break,
+            Ok(LineRead::Stopped) => return Ok(()),
+            Err(e) => {
+                let _ = tx.send(BackgroundMessage::Error(format!("Read error: {}", e)));
+                break;
+            }
+        }
+
+        if text.lines_read.is_multiple_of(1000) {
+            text.report_unparsed();
+            let _ = tx.send(BackgroundMessage::Progress {
+                lines: text.lines_read,
+                percent: 0.0, // no size known for a stream
+            });
+        }
+        // Nothing more has arrived: show the held-back entry instead of waiting for a line
+        // that may not come soon
+        if !reader.buffer().contains(&b'\n') && !text.flush(false) {
+            return Ok(());
+        }
+    }
+
+    if !text.flush(true) {
+        return Ok(());
+    }
+    text.report_unparsed();
+    let _ = tx.send(BackgroundMessage::Completed {
+        total_lines: text.lines_read,
+        entries: text.entries_sent,
+    });
+
+    Ok(())
+}
+
+/// Stream `journalctl -o export` output (e.g. `journalctl -o export -f`) record by record.
+fn read_export(reader: impl BufRead, tx: &Sender<BackgroundMessage>, cmd_rx: &Receiver<BackgroundCommand>) -> anyhow::Result<()> {
+    let mut export = ExportReader::new(reader);
+    let mut records = 0usize;
+
+    loop {
+        if let Ok(cmd) = cmd_rx.try_recv() {
+            match cmd {
+                BackgroundCommand::Cancel | BackgroundCommand::Disconnect => {
+                    return Ok(());
+                }
+            }
+        }
+
+        let fields = match export.next_entry() {
+            Ok(Some(fields)) => fields,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = tx.send(BackgroundMessage::Error(format!("Read error: {}", e)));
+                break;
+            }
+        };
+
+        records += 1;
+        let entry = JournalEntry::from_raw_fields(fields).to_log_entry(records);
+        if tx.send(BackgroundMessage::Entry(entry)).is_err() {
+            return Ok(());
+        }
+
+        if records.is_multiple_of(1000) {
+            let _ = tx.send(BackgroundMessage::Progress {
+                lines: records,
+                percent: 0.0, // no size known for a stream
+            });
+        }
+    }
+
+    let _ = tx.send(BackgroundMessage::Completed {
+        total_lines: records,
+        entries: records,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &[u8]) -> Vec<BackgroundMessage> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (_cmd_tx, cmd_rx) = crossbeam_channel::unbounded();
+        read_stream(BufReader::new(input), &MultilineRules::default(), &ParseOptions::default(), &tx, &cmd_rx).unwrap();
+        drop(tx);
+        rx.into_iter().collect()
+    }
+
+    fn messages(msgs: &[BackgroundMessage]) -> Vec<&str> {
+        msgs.iter()
+            .filter_map(|msg| match msg {
+                BackgroundMessage::Entry(entry) => Some(entry.message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invalid_utf8_does_not_end_the_stream() {
+        let msgs = read(b"Oct 11 16:00:00 host app[1]: caf\xe9\nOct 11 16:00:01 host app[1]: after\n");
+        assert_eq!(messages(&msgs), ["caf\u{FFFD}", "after"]);
+        assert!(!msgs.iter().any(|msg| matches!(msg, BackgroundMessage::Error(_))));
+        assert!(matches!(msgs.last(), Some(BackgroundMessage::Completed { total_lines: 2, entries: 2 })));
+    }
+
+    #[test]
+    fn continuation_lines_fold_and_unknown_lines_stay_raw() {
+        let msgs = read(b"Oct 11 16:00:00 host app[1]: Traceback:\n  File \"x.py\"\nnot a log line\n");
+        assert_eq!(messages(&msgs), ["Traceback:\n  File \"x.py\"", "not a log line"]);
+        assert!(msgs.iter().any(|msg| matches!(msg, BackgroundMessage::ParseFailures { count: 1, .. })));
+    }
+}